@@ -0,0 +1,192 @@
+// Parsing of the ANSI escape sequences in the text, for the raw control chars mode.
+// The SGR (Select Graphic Rendition) sequences are removed from the text and
+// converted to style spans, so that they don't count as visible chars in the
+// pagination or in the search. The other escape sequences are removed.
+
+use crossterm::style::Color;
+
+use crate::style::{StyleSpan, TextStyle};
+
+impl TextStyle {
+    /// Applies the parameters of one SGR sequence, ex: "1;31" of "ESC[1;31m".
+    fn apply_sgr(& mut self, params: &str) {
+        let codes: Vec<u16> = params.split(';')
+                                    .map(|param| param.parse::<u16>().unwrap_or(0))
+                                    .collect();
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = TextStyle::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                code @ 30..=37 => self.fg = Some(Color::AnsiValue((code - 30) as u8)),
+                code @ 40..=47 => self.bg = Some(Color::AnsiValue((code - 40) as u8)),
+                code @ 90..=97 => self.fg = Some(Color::AnsiValue((code - 90 + 8) as u8)),
+                code @ 100..=107 => self.bg = Some(Color::AnsiValue((code - 100 + 8) as u8)),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                code @ (38 | 48) => {
+                    // Extended colors, 256 colors "38;5;n" or true color "38;2;r;g;b".
+                    let color = match codes.get(i + 1) {
+                        Some(5) if i + 2 < codes.len() => {
+                            let color = Color::AnsiValue(codes[i + 2] as u8);
+                            i += 2;
+                            Some(color)
+                        },
+                        Some(2) if i + 4 < codes.len() => {
+                            let color = Color::Rgb {r: codes[i + 2] as u8,
+                                                    g: codes[i + 3] as u8,
+                                                    b: codes[i + 4] as u8};
+                            i += 4;
+                            Some(color)
+                        },
+                        _ => None,
+                    };
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                },
+                // Blink, dim and other attributes are ignored.
+                _ => (),
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Removes the escape sequences from the text and returns the text without
+/// them and the spans of chars that have a style set by SGR sequences.
+pub fn parse_ansi(text_vec: &[char]) -> (Vec<char>, Vec<StyleSpan>) {
+    let mut plain_vec: Vec<char> = Vec::with_capacity(text_vec.len());
+    let mut spans_vec: Vec<StyleSpan> = Vec::new();
+    let mut style = TextStyle::default();
+    let mut span_start = 0_usize;
+
+    let mut i = 0;
+    while i < text_vec.len() {
+        if text_vec[i] != '\x1b' {
+            plain_vec.push(text_vec[i]);
+            i += 1;
+            continue;
+        }
+
+        match text_vec.get(i + 1) {
+            Some('[') => {
+                // CSI sequence, the parameters end at a final byte between '@' and '~'.
+                let mut end = i + 2;
+                while end < text_vec.len() && !('@'..='~').contains(&text_vec[end]) {
+                    end += 1;
+                }
+                if end < text_vec.len() && text_vec[end] == 'm' {
+                    let params: String = text_vec[i + 2..end].iter().collect();
+                    let mut new_style = style;
+                    new_style.apply_sgr(&params);
+                    if new_style != style {
+                        // Closes the span of the previous style.
+                        if style != TextStyle::default() && plain_vec.len() > span_start {
+                            spans_vec.push(StyleSpan {start_pos: span_start,
+                                                      end_pos: plain_vec.len() - 1,
+                                                      style});
+                        }
+                        style = new_style;
+                        span_start = plain_vec.len();
+                    }
+                }
+                i = end + 1;
+            },
+            Some(']') => {
+                // OSC sequence, ends with BEL or with ESC '\'.
+                let mut end = i + 2;
+                while end < text_vec.len() && text_vec[end] != '\x07'
+                      && !(text_vec[end] == '\x1b' && text_vec.get(end + 1) == Some(&'\\')) {
+                    end += 1;
+                }
+                i = if end < text_vec.len() && text_vec[end] == '\x1b' { end + 2 } else { end + 1 };
+            },
+            // Two chars escape sequence.
+            Some(_) => i += 2,
+            None => i += 1,
+        }
+    }
+
+    // Closes the span of the last style.
+    if style != TextStyle::default() && plain_vec.len() > span_start {
+        spans_vec.push(StyleSpan {start_pos: span_start,
+                                  end_pos: plain_vec.len() - 1,
+                                  style});
+    }
+
+    (plain_vec, spans_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> (String, Vec<(usize, usize, TextStyle)>) {
+        let text_vec: Vec<char> = text.chars().collect();
+        let (plain_vec, spans_vec) = parse_ansi(&text_vec);
+        (plain_vec.iter().collect(),
+         spans_vec.iter().map(|span| (span.start_pos, span.end_pos, span.style)).collect())
+    }
+
+    fn sgr(params: &str) -> TextStyle {
+        let mut style = TextStyle::default();
+        style.apply_sgr(params);
+        style
+    }
+
+    #[test]
+    fn reset_closes_the_span() {
+        let bold = TextStyle {bold: true, ..TextStyle::default()};
+        assert_eq!(parse("a\x1b[1mbc\x1b[0md"), ("abcd".to_string(), vec![(1, 2, bold)]));
+        // An empty SGR sequence is also a reset.
+        assert_eq!(parse("\x1b[1mab\x1b[mc"), ("abc".to_string(), vec![(0, 1, bold)]));
+    }
+
+    #[test]
+    fn nested_attributes() {
+        let bold = TextStyle {bold: true, ..TextStyle::default()};
+        let bold_red = TextStyle {fg: Some(Color::AnsiValue(1)), ..bold};
+        let red = TextStyle {fg: Some(Color::AnsiValue(1)), ..TextStyle::default()};
+        assert_eq!(parse("\x1b[1ma\x1b[31mb\x1b[22mc\x1b[39md"),
+                   ("abcd".to_string(), vec![(0, 0, bold), (1, 1, bold_red), (2, 2, red)]));
+    }
+
+    #[test]
+    fn extended_colors() {
+        assert_eq!(sgr("38;5;208").fg, Some(Color::AnsiValue(208)));
+        assert_eq!(sgr("48;2;10;20;30").bg, Some(Color::Rgb {r: 10, g: 20, b: 30}));
+        // The codes after the color are applied.
+        assert_eq!(sgr("38;2;1;2;3;4"), TextStyle {fg: Some(Color::Rgb {r: 1, g: 2, b: 3}),
+                                                   underline: true,
+                                                   ..TextStyle::default()});
+        // A color without all its parameters isn't set.
+        assert_eq!(sgr("38;5").fg, None);
+    }
+
+    #[test]
+    fn other_sequences_are_removed() {
+        // Cursor movement and erase CSI sequences.
+        assert_eq!(parse("a\x1b[2Kb\x1b[10;5Hc"), ("abc".to_string(), vec![]));
+        // OSC sequences ended with BEL or with ESC '\', ex: a title or a hyperlink.
+        assert_eq!(parse("\x1b]0;title\x07a\x1b]8;;http://x\x1b\\b"), ("ab".to_string(), vec![]));
+        // Two chars escape sequence.
+        assert_eq!(parse("a\x1b=b"), ("ab".to_string(), vec![]));
+    }
+
+    #[test]
+    fn truncated_sequence_at_the_end() {
+        assert_eq!(parse("abc\x1b[1;3"), ("abc".to_string(), vec![]));
+        assert_eq!(parse("abc\x1b]0;title"), ("abc".to_string(), vec![]));
+        assert_eq!(parse("abc\x1b"), ("abc".to_string(), vec![]));
+    }
+}
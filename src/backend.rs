@@ -0,0 +1,95 @@
+// The terminal where the pages are drawn and where the keys are read, the
+// terminal of the standard output, or the terminal in memory of the tests.
+
+use std::io::stdout;
+use std::time::Duration;
+
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event},
+    execute,
+    style::{Colors, Print, SetColors, Stylize},
+    terminal, Result,
+};
+
+/// The colors and the attributes of the chars written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CellStyle {
+    pub colors: Colors,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl CellStyle {
+    /// The colors without attributes.
+    pub fn plain(colors: Colors) -> Self {
+        CellStyle { colors, bold: false, italic: false, underline: false, reverse: false }
+    }
+
+    pub fn bold(colors: Colors) -> Self {
+        CellStyle { bold: true, ..CellStyle::plain(colors) }
+    }
+}
+
+pub trait Terminal {
+    /// The (columns, rows) of the terminal.
+    fn size(&self) -> Result<(u16, u16)>;
+    fn move_to(& mut self, column: u16, row: u16) -> Result<()>;
+    /// Writes the text from the cursor, the cursor goes after it.
+    fn print(& mut self, text: &str, style: CellStyle) -> Result<()>;
+    /// Clears all the screen with the colors.
+    fn clear(& mut self, colors: Colors) -> Result<()>;
+    /// Clears the rest of the row of the cursor with the colors.
+    fn clear_until_new_line(& mut self, colors: Colors) -> Result<()>;
+    /// Waits for an event during the time, returns true when there is one to read.
+    fn poll(& mut self, timeout: Duration) -> Result<bool>;
+    fn read(& mut self) -> Result<Event>;
+}
+
+/// The terminal of the standard output.
+pub struct CrosstermTerminal;
+
+impl Terminal for CrosstermTerminal {
+    fn size(&self) -> Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn move_to(& mut self, column: u16, row: u16) -> Result<()> {
+        execute!(stdout(), MoveTo(column, row))
+    }
+
+    fn print(& mut self, text: &str, style: CellStyle) -> Result<()> {
+        let mut content = text.stylize();
+        if style.bold {
+            content = content.bold();
+        }
+        if style.italic {
+            content = content.italic();
+        }
+        if style.underline {
+            content = content.underlined();
+        }
+        if style.reverse {
+            content = content.reverse();
+        }
+        execute!(stdout(), SetColors(style.colors), Print(content))
+    }
+
+    fn clear(& mut self, colors: Colors) -> Result<()> {
+        execute!(stdout(), SetColors(colors), terminal::Clear(terminal::ClearType::All))
+    }
+
+    fn clear_until_new_line(& mut self, colors: Colors) -> Result<()> {
+        execute!(stdout(), SetColors(colors), terminal::Clear(terminal::ClearType::UntilNewLine))
+    }
+
+    fn poll(& mut self, timeout: Duration) -> Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(& mut self) -> Result<Event> {
+        event::read()
+    }
+}
@@ -0,0 +1,96 @@
+// The configuration file with the defaults and the key bindings, and the
+// LESS_FP environment variable with the default command line flags.
+//
+// The config file is read from $XDG_CONFIG_HOME/less_fp/config.toml or from
+// ~/.config/less_fp/config.toml, ex:
+//
+//     theme = "amber"
+//     fixation = "third"
+//     max_width = 70
+//     center = true
+//
+//     [search]
+//     ignore_case = true
+//     wrap_around = true
+//
+//     [keys]
+//     next_page = ["a", "Space", "PageDown"]
+//     prev_page = ["q", "PageUp"]
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::keymap::KeySpec;
+use less_fp::FixationStrategy;
+
+pub const ENV_DEFAULT_FLAGS: &str = "LESS_FP";
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Option<String>,
+    pub fixation: FixationStrategy,
+    pub no_fixation: bool,
+    pub max_width: Option<u16>,
+    pub center: bool,
+    pub margin: u16,
+    pub line_numbers: bool,
+    pub reflow: bool,
+    pub raw_control_chars: bool,
+    pub no_init: bool,
+    pub no_resume: bool,
+    pub wpm: Option<u16>,
+    pub auto_advance: bool,
+    pub preprocessor: Option<String>,
+    pub search: SearchOptions,
+    pub keys: BTreeMap<String, KeySpec>,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchOptions {
+    pub ignore_case: bool,
+    pub wrap_around: bool,
+}
+
+/// The directory of the config file, following the XDG Base Directory specification.
+pub fn config_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("less_fp")),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("less_fp")),
+    }
+}
+
+/// Reads the config file in the path, or in the default path. A missing
+/// default config file isn't an error, all the values take their defaults.
+pub fn load(path: Option<&Path>) -> Result<Config, String> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match config_dir() {
+            Some(dir) if dir.join("config.toml").exists() => dir.join("config.toml"),
+            _ => return Ok(Config::default()),
+        },
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("While reading the config file '{}' ... {}", path.to_string_lossy(), err))?;
+    toml::from_str(&text)
+        .map_err(|err| format!("In the config file '{}' ... {}", path.to_string_lossy(), err))
+}
+
+/// The command line arguments, with the default flags of the LESS_FP
+/// environment variable inserted before the arguments of the command line,
+/// so that the command line overrides them.
+pub fn args_with_env_flags() -> Vec<OsString> {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    if let Some(flags) = env::var_os(ENV_DEFAULT_FLAGS) {
+        let position = 1.min(args.len());
+        let flags = flags.to_string_lossy().into_owned();
+        args.splice(position..position, flags.split_whitespace().map(OsString::from));
+    }
+    args
+}
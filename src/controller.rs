@@ -0,0 +1,308 @@
+// The reading session around the viewer, with the commands that don't only
+// change the pages: the marks, the table of contents, the pacing and its
+// speed, the RSVP view, the statistics, the message of the fixation points
+// and the quit. The keys are translated to these commands, and the changes
+// of the pages are given to the viewer.
+
+use std::time::Duration;
+
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    Result,
+};
+
+use crate::backend::{CellStyle, Terminal};
+use crate::keymap::Action;
+use crate::pacing::{count_words, Pacing};
+use crate::render::{print_guide, print_message, print_status, render};
+use crate::rsvp;
+use crate::search::SearchMode;
+use crate::state::Bookmarks;
+use crate::stats::{self, ReadingStats};
+use crate::toc::{self, Chapter};
+use crate::viewer::{Command, Viewer};
+
+/// A command of the reading session.
+pub enum SessionCommand {
+    // A change of the pages.
+    View(Command),
+    // The time of the step of the reading guide, or of the page, is over.
+    NextStep,
+    // Hides or shows the fixation points, with a message.
+    ToggleFixation,
+    // The next key is the letter of the mark to set, or to go to.
+    PromptMark(Action),
+    SetMark(char),
+    GoToMark(char),
+    // Any other key after the prompt of the mark.
+    CancelMark,
+    TableOfContents,
+    TogglePacing,
+    Faster,
+    Slower,
+    ToggleRsvp,
+    Statistics,
+    // Saves the last reading position and the session, and ends the reading.
+    Quit,
+}
+
+pub struct Controller<'a> {
+    chapters: &'a [Chapter],
+    bookmarks: &'a mut Bookmarks,
+    pacing: Pacing,
+    reading_stats: ReadingStats,
+    // After the key that sets a mark or goes to a mark, the key of the letter of the mark.
+    pending_mark: Option<Action>,
+}
+
+impl<'a> Controller<'a> {
+    /// Starts the session, with the pacing of the settings of the viewer.
+    pub fn new(viewer: &Viewer, chapters: &'a [Chapter], bookmarks: &'a mut Bookmarks) -> Self {
+        Controller {
+            chapters,
+            bookmarks,
+            pacing: Pacing::new(viewer.settings.wpm, viewer.settings.auto_advance),
+            reading_stats: ReadingStats::start(),
+            pending_mark: None,
+        }
+    }
+
+    /// Draws the step of the reading guide and the status of the pacing, and
+    /// returns the time until the next step, or None when only the keys change the page.
+    pub fn show_step(& mut self, term: & mut dyn Terminal, viewer: & mut Viewer) -> Result<Option<Duration>> {
+        // In the pacing mode, the time of the page is the time to read its words,
+        // and with the reading guide, the time of its step, but not while the
+        // search string is entered.
+        let entering_search = matches!(viewer.search_mode, SearchMode::EnteringSearchString);
+        let (page_num, page) = viewer.pages_vec.get_curr_page();
+        let page_start_pos = page.global_start_char_pos;
+        let guide_steps_vec = viewer.guide_steps();
+        let num_words = count_words(viewer.page_text());
+        let time_left = match (entering_search, viewer.guide.as_mut()) {
+            (true, _) => None,
+            (false, Some(guide)) => guide.time_left(page_num, &guide_steps_vec, self.pacing.wpm, self.pacing.running),
+            (false, None) => self.pacing.time_left(page_num, num_words),
+        };
+        self.reading_stats.page_shown(page_start_pos, num_words, viewer.settings.no_fixation);
+
+        let guide_step = viewer.guide.as_ref().and_then(|guide| guide.current_step(&guide_steps_vec));
+        if let (Some(step), false) = (guide_step, entering_search) {
+            print_guide(term, viewer, step, true)?;
+        }
+        let status = match viewer.guide {
+            Some(ref guide) => Some(guide.status(self.pacing.wpm, self.pacing.running)),
+            None => self.pacing.status(),
+        };
+        if let Some(status) = status {
+            print_status(term, &status, &viewer.settings.theme)?;
+        }
+        Ok(time_left)
+    }
+
+    /// The command of the event, or None when the event isn't a bound key.
+    pub fn command(&self, viewer: &Viewer, event: &Event) -> Option<SessionCommand> {
+        // The letter of the mark, any other key cancels.
+        if let (Some(mark_action), Event::Key(key_event)) = (self.pending_mark, event) {
+            return Some(match key_event.code {
+                KeyCode::Char(letter) if letter.is_ascii_alphabetic() => {
+                        if mark_action == Action::SetMark {
+                            SessionCommand::SetMark(letter)
+                        } else {
+                            SessionCommand::GoToMark(letter)
+                        }
+                    },
+                _ => SessionCommand::CancelMark,
+            });
+        }
+
+        // Ctrl-C always quits, because the raw mode doesn't let it send the interrupt signal.
+        let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+
+        // The keys are the search string, before the key bindings, so a letter
+        // bound to an action can be searched. Only Esc and Ctrl-C quit, and the
+        // Enter key exits the search mode.
+        if let SearchMode::EnteringSearchString = viewer.search_mode {
+            let Event::Key(key_event) = event else {
+                return None;
+            };
+            if key_event.code == KeyCode::Esc || *event == ctrl_c {
+                return Some(SessionCommand::Quit);
+            }
+            let command = match key_event.code {
+                KeyCode::Enter => Command::SubmitSearch,
+                KeyCode::Backspace => Command::SearchBackspace,
+                KeyCode::Char(c) => Command::SearchInput(c),
+                _ => return None,
+            };
+            return Some(SessionCommand::View(command));
+        }
+
+        if *event == ctrl_c {
+            return Some(SessionCommand::Quit);
+        }
+        let command = match viewer.settings.keymap.action(event)? {
+            Action::Quit => SessionCommand::Quit,
+            Action::PrevPage => SessionCommand::View(Command::PrevPage),
+            Action::NextPage => SessionCommand::View(Command::NextPage),
+            Action::Search => SessionCommand::View(Command::StartSearch),
+            Action::PrevMatch => SessionCommand::View(Command::PrevMatch),
+            Action::NextMatch => SessionCommand::View(Command::NextMatch),
+            Action::NarrowColumn => SessionCommand::View(Command::NarrowColumn),
+            Action::WidenColumn => SessionCommand::View(Command::WidenColumn),
+            Action::ToggleCenter => SessionCommand::View(Command::ToggleCenter),
+            Action::ToggleLineNumbers => SessionCommand::View(Command::ToggleLineNumbers),
+            Action::ToggleGuide => SessionCommand::View(Command::ToggleGuide),
+            Action::ToggleFixation => SessionCommand::ToggleFixation,
+            Action::TableOfContents => SessionCommand::TableOfContents,
+            mark_action @ (Action::SetMark | Action::GoToMark) => SessionCommand::PromptMark(mark_action),
+            Action::TogglePacing => SessionCommand::TogglePacing,
+            Action::Faster => SessionCommand::Faster,
+            Action::Slower => SessionCommand::Slower,
+            Action::ToggleRsvp => SessionCommand::ToggleRsvp,
+            Action::Statistics => SessionCommand::Statistics,
+        };
+        Some(command)
+    }
+
+    /// Runs the command and draws its result, returns false when the reading is over.
+    pub fn apply(& mut self, term: & mut dyn Terminal, viewer: & mut Viewer, command: SessionCommand) -> Result<bool> {
+        let (_page_num, page) = viewer.pages_vec.get_curr_page();
+        let page_start_pos = page.global_start_char_pos;
+        match command {
+            SessionCommand::View(command) => {
+                    if viewer.apply(command) {
+                        render(term, viewer)?;
+                    }
+                },
+            SessionCommand::NextStep => {
+                    // The guide goes to the next step, and at the last step of the page the pacing goes to the next page.
+                    let guide_steps_vec = viewer.guide_steps();
+                    let prev_step = viewer.guide.as_mut().and_then(|guide| {
+                        let step = guide.current_step(&guide_steps_vec)?;
+                        guide.next_step(guide_steps_vec.len()).then_some(step)
+                    });
+                    if let Some(step) = prev_step {
+                        print_guide(term, viewer, step, false)?;
+                    } else {
+                        // Goes to the next page, and stops at the last one.
+                        if !viewer.apply(Command::NextPage) {
+                            self.pacing.toggle();
+                        }
+                        render(term, viewer)?;
+                    }
+                },
+            SessionCommand::ToggleFixation => {
+                    viewer.apply(Command::ToggleFixation);
+                    render(term, viewer)?;
+                    let message = if viewer.settings.no_fixation { "Fixation points off" } else { "Fixation points on" };
+                    print_message(term, message, &viewer.settings.theme)?;
+                },
+            SessionCommand::PromptMark(mark_action) => {
+                    self.pending_mark = Some(mark_action);
+                    let prompt = if mark_action == Action::SetMark { "Set mark: " } else { "Go to mark: " };
+                    print_message(term, prompt, &viewer.settings.theme)?;
+                },
+            SessionCommand::SetMark(letter) => {
+                    self.pending_mark = None;
+                    let message = match self.bookmarks.set_mark(letter, page_start_pos) {
+                        Ok(()) => format!("Mark '{}' set", letter),
+                        Err(err) => format!("Mark '{}' set, but not saved ... {}", letter, err),
+                    };
+                    render(term, viewer)?;
+                    print_message(term, &message, &viewer.settings.theme)?;
+                },
+            SessionCommand::GoToMark(letter) => {
+                    self.pending_mark = None;
+                    let mark_pos = self.bookmarks.mark(letter);
+                    if let Some(mark_pos) = mark_pos {
+                        viewer.apply(Command::GoToPos(mark_pos));
+                    }
+                    render(term, viewer)?;
+                    if mark_pos.is_none() {
+                        print_message(term, &format!("Mark '{}' isn't set", letter), &viewer.settings.theme)?;
+                    }
+                },
+            SessionCommand::CancelMark => {
+                    self.pending_mark = None;
+                    render(term, viewer)?;
+                },
+            // Shows the table of contents and goes to the chosen chapter.
+            SessionCommand::TableOfContents => {
+                    if self.chapters.is_empty() {
+                        return Ok(true);
+                    }
+                    let curr_chapter = toc::chapter_at(self.chapters, page_start_pos);
+                    self.reading_stats.page_left();
+                    let chosen = toc::choose_chapter(term, self.chapters, curr_chapter, &viewer.settings.theme,
+                                                     &viewer.settings.keymap)?;
+
+                    // The terminal could have been resized while choosing.
+                    let (len_col, len_row) = term.size()?;
+                    viewer.apply(Command::Resize(len_col, len_row));
+                    if let Some(chapter_index) = chosen {
+                        viewer.apply(Command::GoToPos(self.chapters[chapter_index].start_pos));
+                    }
+                    render(term, viewer)?;
+                },
+            // Starts or pauses the pacing mode, or changes its speed.
+            SessionCommand::TogglePacing => {
+                    self.pacing.toggle();
+                    if let Some(ref mut guide) = viewer.guide {
+                        guide.restart_step();
+                    }
+                },
+            SessionCommand::Faster => self.pacing.faster(),
+            SessionCommand::Slower => self.pacing.slower(),
+            // Shows the words one at a time, from the word of the reading guide or from the start of
+            // the page, and back in the pages at the page of the last word shown.
+            SessionCommand::ToggleRsvp => {
+                    let guide_steps_vec = viewer.guide_steps();
+                    let start_pos = match viewer.guide.as_ref().and_then(|guide| guide.current_step(&guide_steps_vec)) {
+                        Some(step) => page_start_pos + step.start,
+                        None => page_start_pos,
+                    };
+                    self.reading_stats.page_left();
+                    let word_pos = rsvp::show_rsvp(term, viewer.text_vec, start_pos, viewer.settings.fixation,
+                                                   & mut self.pacing, &viewer.settings.theme, &viewer.settings.keymap)?;
+
+                    // The terminal could have been resized in the view.
+                    let (len_col, len_row) = term.size()?;
+                    viewer.apply(Command::Resize(len_col, len_row));
+                    viewer.apply(Command::GoToPos(word_pos));
+                    render(term, viewer)?;
+                },
+            // Shows the reading statistics of the session and of the file.
+            SessionCommand::Statistics => {
+                    self.reading_stats.page_left();
+                    stats::show_stats(term, &self.reading_stats.session, &self.bookmarks.file_state.sessions,
+                                      &viewer.settings.theme)?;
+
+                    // The terminal could have been resized while the statistics were shown.
+                    let (len_col, len_row) = term.size()?;
+                    viewer.apply(Command::Resize(len_col, len_row));
+                    render(term, viewer)?;
+                },
+            SessionCommand::Quit => {
+                    // The last reading position, an error only loses it.
+                    if viewer.settings.resume {
+                        let _ = self.bookmarks.set_last_pos(page_start_pos);
+                    }
+                    // The reading session, an error only loses it.
+                    let session = std::mem::replace(& mut self.reading_stats, ReadingStats::start()).end_session();
+                    if !session.pages.is_empty() {
+                        let _ = self.bookmarks.add_session(session);
+                    }
+
+                    if viewer.settings.no_init {
+                        // The last page stays on the screen, above the prompt of the shell.
+                        let (_len_col, len_row) = term.size()?;
+                        term.move_to(0, len_row.saturating_sub(1))?;
+                        term.print("\r\n", CellStyle::plain(viewer.settings.theme.text))?;
+                    }
+                    // Otherwise, leaving the alternate screen restores the contents of the screen.
+                    return Ok(false);
+                },
+        }
+        Ok(true)
+    }
+}
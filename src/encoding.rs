@@ -0,0 +1,135 @@
+// Detection of the encoding of the text and decoding to a String.
+// The BOM is sniffed first, then UTF-16 without BOM, then UTF-8, and the
+// fallback is Windows-1252, a superset of Latin-1, common in old e-texts.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+/// The number of bytes, or chars, at the start of the text used in the detections.
+const SNIFF_LEN: usize = 4096;
+/// The number of chars under which a few control chars don't make the text binary.
+const MIN_BINARY_SAMPLE: usize = 64;
+
+pub struct Decoded {
+    pub text: String,
+    pub encoding_name: &'static str,
+}
+
+/// Decodes the bytes with the encoding of the label, ex: "latin1", or with the detected encoding.
+pub fn decode(bytes: &[u8], encoding_label: Option<&str>) -> Result<Decoded, String> {
+    let encoding = match encoding_label {
+        Some(label) => Encoding::for_label(label.trim().as_bytes())
+                           .ok_or(format!("Unknown encoding '{}'", label))?,
+        None => detect_encoding(bytes),
+    };
+    let (text, _had_errors) = encoding.decode_with_bom_removal(bytes);
+    Ok(Decoded {
+        text: text.into_owned(),
+        encoding_name: encoding.name(),
+    })
+}
+
+fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _bom_len)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+
+    // UTF-16 text without BOM, mostly ASCII, has a zero byte in every other byte.
+    let sniff = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let num_pairs = sniff.len() / 2;
+    if num_pairs > 0 {
+        let zeros_even = sniff.iter().step_by(2).filter(|b| **b == 0).count();
+        let zeros_odd = sniff.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+        if zeros_odd * 10 > num_pairs * 7 && zeros_even * 10 < num_pairs {
+            return UTF_16LE;
+        }
+        if zeros_even * 10 > num_pairs * 7 && zeros_odd * 10 < num_pairs {
+            return UTF_16BE;
+        }
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+/// The text seems to be binary, when it has NUL chars or many control chars,
+/// that are only counted in a text long enough.
+pub fn is_binary(text: &str) -> bool {
+    let mut num_chars = 0_usize;
+    let mut num_control = 0_usize;
+    for c in text.chars().take(SNIFF_LEN) {
+        num_chars += 1;
+        if c == '\0' {
+            return true;
+        }
+        if c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\x0c' | '\x1b' | '\x08') {
+            num_control += 1;
+        }
+    }
+    num_chars >= MIN_BINARY_SAMPLE && num_control * 10 > num_chars
+}
+
+/// The chars shown on the screen for a control char, in caret notation, ex:
+/// "^A" for 0x01 and "^?" for DEL, or in hex for the C1 controls, ex: "<85>".
+pub fn escape_control_char(c: char) -> Option<String> {
+    match c as u32 {
+        code @ 0x00..=0x1f => Some(format!("^{}", char::from_u32(code + 0x40).unwrap())),
+        0x7f => Some("^?".to_string()),
+        code @ 0x80..=0x9f => Some(format!("<{:02X}>", code)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() })
+            .collect()
+    }
+
+    #[test]
+    fn bom_is_sniffed_and_removed() {
+        let decoded = decode(b"\xef\xbb\xbfcaf\xc3\xa9", None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding_name), ("café", "UTF-8"));
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend(utf16("café", false));
+        let decoded = decode(&bytes, None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding_name), ("café", "UTF-16LE"));
+    }
+
+    #[test]
+    fn utf16_without_bom() {
+        let decoded = decode(&utf16("Alice was beginning", false), None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding_name), ("Alice was beginning", "UTF-16LE"));
+        let decoded = decode(&utf16("Alice was beginning", true), None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding_name), ("Alice was beginning", "UTF-16BE"));
+    }
+
+    #[test]
+    fn invalid_utf8_falls_back_to_latin1() {
+        let decoded = decode(b"caf\xe9 cr\xe8me", None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding_name), ("café crème", "windows-1252"));
+    }
+
+    #[test]
+    fn encoding_label_overrides_the_detection() {
+        // Valid UTF-8, read as Latin-1.
+        let decoded = decode(b"caf\xc3\xa9", Some("latin1")).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.encoding_name), ("cafÃ©", "windows-1252"));
+        assert!(decode(b"cafe", Some("klingon")).is_err());
+    }
+
+    #[test]
+    fn binary_text() {
+        assert!(is_binary("ELF\0\x01"));
+        assert!(is_binary(&"\x01\x02\x03 data ".repeat(10)));
+        // A few control chars in a short text.
+        assert!(!is_binary("caf\u{e9}\x01x\n"));
+        assert!(!is_binary(&"A line of text.\n".repeat(10)));
+    }
+}
@@ -0,0 +1,318 @@
+// The EPUB books, a zip container of XHTML chapters. The container.xml gives
+// the path of the package document (OPF), the spine of the package document
+// lists the chapters in the reading order, and the table of contents, the
+// EPUB 3 navigation document or the EPUB 2 NCX, gives their titles.
+
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read};
+
+use roxmltree::{Document, ParsingOptions};
+use zip::ZipArchive;
+
+use crate::html::html_to_text;
+use crate::style::StyleSpan;
+use crate::toc::Chapter;
+
+/// The length of the chapter titles taken from the text, when the book has no table of contents.
+const MAX_TITLE_LEN: usize = 60;
+
+pub struct Book {
+    pub text_vec: Vec<char>,
+    pub styles_vec: Vec<StyleSpan>,
+    pub chapters: Vec<Chapter>,
+}
+
+/// The EPUB container is a zip file, with the uncompressed mimetype file first.
+pub fn is_epub(bytes: &[u8]) -> bool {
+    if !bytes.starts_with(b"PK\x03\x04") || bytes.get(30..38) != Some(b"mimetype") {
+        return false;
+    }
+    let extra_len = u16::from_le_bytes([bytes[28], bytes[29]]) as usize;
+    let mimetype = b"application/epub+zip";
+    bytes.get(38 + extra_len..38 + extra_len + mimetype.len()) == Some(mimetype)
+}
+
+/// Reads the chapters of the spine, in order, to one text.
+pub fn read_epub(bytes: &[u8]) -> io::Result<Book> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(invalid_data)?;
+
+    let container = read_entry(& mut archive, "META-INF/container.xml")?;
+    let container_doc = parse_xml(&container)?;
+    let opf_path = container_doc.descendants()
+                                .find(|node| node.has_tag_name("rootfile"))
+                                .and_then(|node| node.attribute("full-path"))
+                                .ok_or_else(|| invalid_data("no package document in the container.xml"))?
+                                .to_string();
+    let opf_dir = parent_dir(&opf_path);
+    let opf = read_entry(& mut archive, &opf_path)?;
+    let opf_doc = parse_xml(&opf)?;
+
+    // The manifest items by id, with their paths in the zip file and their properties.
+    let mut manifest: HashMap<&str, (String, &str)> = HashMap::new();
+    for item in opf_doc.descendants().filter(|node| node.has_tag_name("item")) {
+        if let (Some(id), Some(href)) = (item.attribute("id"), item.attribute("href")) {
+            manifest.insert(id, (resolve_href(opf_dir, href), item.attribute("properties").unwrap_or("")));
+        }
+    }
+    let spine = opf_doc.descendants()
+                       .find(|node| node.has_tag_name("spine"))
+                       .ok_or_else(|| invalid_data("no spine in the package document"))?;
+
+    // The chapter titles by the path of their files, from the navigation document or from the NCX.
+    let mut titles: HashMap<String, String> = HashMap::new();
+    let nav_path = manifest.values()
+                           .find(|(_path, properties)| properties.split_whitespace().any(|p| p == "nav"))
+                           .map(|(path, _properties)| path.clone());
+    if let Some(nav_path) = nav_path {
+        if let Ok(nav) = read_entry(& mut archive, &nav_path) {
+            titles = nav_titles(&nav, parent_dir(&nav_path));
+        }
+    }
+    let ncx_path = spine.attribute("toc").and_then(|id| manifest.get(id)).map(|(path, _properties)| path.clone());
+    if let (true, Some(ncx_path)) = (titles.is_empty(), ncx_path) {
+        if let Ok(ncx) = read_entry(& mut archive, &ncx_path) {
+            titles = ncx_titles(&ncx, parent_dir(&ncx_path));
+        }
+    }
+
+    let mut book = Book { text_vec: Vec::new(), styles_vec: Vec::new(), chapters: Vec::new() };
+    let idrefs: Vec<&str> = spine.children()
+                                 .filter(|node| node.has_tag_name("itemref"))
+                                 .filter_map(|node| node.attribute("idref"))
+                                 .collect();
+    for idref in idrefs {
+        let path = match manifest.get(idref) {
+            Some((path, _properties)) => path,
+            None => continue,
+        };
+        let xhtml = read_entry(& mut archive, path)?;
+        let (chapter_vec, chapter_styles) = html_to_text(&xhtml);
+        // The text of the chapter starts after the blank line between the chapters.
+        let start_pos = if book.text_vec.is_empty() { 0 } else { book.text_vec.len() + 1 };
+        let title = match titles.get(path) {
+            Some(title) => Some(title.clone()),
+            // Without a table of contents, each file is a chapter and its title is its first line.
+            None if titles.is_empty() => {
+                let first_line: String = chapter_vec.iter().take_while(|c| **c != '\n').take(MAX_TITLE_LEN).collect();
+                Some(if first_line.trim().is_empty() {
+                         format!("Chapter {}", book.chapters.len() + 1)
+                     } else {
+                         first_line.trim().to_string()
+                     })
+            },
+            None => None,
+        };
+        if let Some(title) = title {
+            book.chapters.push(Chapter { title, start_pos });
+        }
+        if chapter_vec.iter().all(|c| c.is_whitespace()) {
+            continue;
+        }
+        if !book.text_vec.is_empty() {
+            // A blank line between the chapters.
+            book.text_vec.push('\n');
+        }
+        let offset = book.text_vec.len();
+        book.styles_vec.extend(chapter_styles.into_iter().map(|span| StyleSpan {
+            start_pos: span.start_pos + offset,
+            end_pos: span.end_pos + offset,
+            style: span.style,
+        }));
+        book.text_vec.extend(chapter_vec);
+    }
+
+    if book.text_vec.is_empty() {
+        book.text_vec.push('\n');
+    }
+    // The chapters without text start at the next chapter, or at the end of the book.
+    let last_pos = book.text_vec.len() - 1;
+    for chapter in book.chapters.iter_mut() {
+        chapter.start_pos = chapter.start_pos.min(last_pos);
+    }
+    Ok(book)
+}
+
+/// The titles of the EPUB 3 navigation document, the links of the "toc" nav element.
+/// The chapters start at the files, so the titles are keyed by the path without the
+/// fragment, and a file with several entries, ex: its sections, keeps the first one.
+fn nav_titles(nav: &str, nav_dir: &str) -> HashMap<String, String> {
+    let mut titles = HashMap::new();
+    let nav_doc = match parse_xml(nav) {
+        Ok(nav_doc) => nav_doc,
+        Err(_) => return titles,
+    };
+    let toc_nav = nav_doc.descendants()
+                         .filter(|node| node.has_tag_name("nav"))
+                         .find(|node| node.attributes().any(|attr| attr.name() == "type" && attr.value() == "toc"));
+    if let Some(toc_nav) = toc_nav {
+        for link in toc_nav.descendants().filter(|node| node.has_tag_name("a")) {
+            if let Some(href) = link.attribute("href") {
+                let title = node_text(link);
+                titles.entry(resolve_href(nav_dir, href)).or_insert(title);
+            }
+        }
+    }
+    titles
+}
+
+/// The titles of the EPUB 2 NCX, the labels of the navigation points, the first one of each file.
+fn ncx_titles(ncx: &str, ncx_dir: &str) -> HashMap<String, String> {
+    let mut titles = HashMap::new();
+    let ncx_doc = match parse_xml(ncx) {
+        Ok(ncx_doc) => ncx_doc,
+        Err(_) => return titles,
+    };
+    for nav_point in ncx_doc.descendants().filter(|node| node.has_tag_name("navPoint")) {
+        let label = nav_point.children().find(|node| node.has_tag_name("navLabel"));
+        let content = nav_point.children().find(|node| node.has_tag_name("content"));
+        if let (Some(label), Some(src)) = (label, content.and_then(|node| node.attribute("src"))) {
+            titles.entry(resolve_href(ncx_dir, src)).or_insert_with(|| node_text(label));
+        }
+    }
+    titles
+}
+
+/// The text inside the node, with the whitespace collapsed.
+fn node_text(node: roxmltree::Node<'_, '_>) -> String {
+    let text: String = node.descendants().filter(|node| node.is_text()).filter_map(|node| node.text()).collect();
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn read_entry(archive: & mut ZipArchive<Cursor<&[u8]>>, path: &str) -> io::Result<String> {
+    let mut entry = archive.by_name(path)
+                           .map_err(|err| invalid_data(format!("{} in the EPUB: {}", path, err)))?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(& mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn parse_xml(text: &str) -> io::Result<Document<'_>> {
+    let options = ParsingOptions { allow_dtd: true, ..ParsingOptions::default() };
+    Document::parse_with_options(text, options).map_err(invalid_data)
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// The directory of the path in the zip file, ex: "OEBPS" of "OEBPS/content.opf".
+fn parent_dir(path: &str) -> &str {
+    path.rfind('/').map_or("", |end| &path[..end])
+}
+
+/// The path in the zip file of the href relative to the directory, without the fragment.
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut segments: Vec<String> = base_dir.split('/')
+                                            .filter(|segment| !segment.is_empty())
+                                            .map(String::from)
+                                            .collect();
+    for segment in percent_decode(href).split('/') {
+        match segment {
+            "" | "." => (),
+            ".." => { segments.pop(); },
+            _ => segments.push(segment.to_string()),
+        }
+    }
+    segments.join("/")
+}
+
+/// Decodes the escaped bytes of an URL, ex: "%20".
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+                       .and_then(|hex| std::str::from_utf8(hex).ok())
+                       .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                    decoded.push(byte);
+                    i += 3;
+                },
+            (byte, _) => {
+                    decoded.push(byte);
+                    i += 1;
+                },
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    /// An EPUB with the files, after the mimetype file.
+    fn build_epub(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("mimetype", options).unwrap();
+        writer.write_all(b"application/epub+zip").unwrap();
+        for (path, content) in files {
+            writer.start_file(*path, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn chapter(body: &str) -> String {
+        format!("<html xmlns=\"http://www.w3.org/1999/xhtml\"><body>{}</body></html>", body)
+    }
+
+    #[test]
+    fn chapters_start_at_their_text() {
+        let container = r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#;
+        let opf = r#"<package>
+                       <manifest>
+                         <item id="nav" href="nav.xhtml" properties="nav"/>
+                         <item id="c1" href="text/one.xhtml"/>
+                         <item id="c2" href="text/two.xhtml"/>
+                         <item id="c3" href="text/three.xhtml"/>
+                       </manifest>
+                       <spine><itemref idref="c1"/><itemref idref="c2"/><itemref idref="c3"/></spine>
+                     </package>"#;
+        let nav = r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops"><body>
+                       <nav epub:type="toc"><ol>
+                         <li><a href="text/one.xhtml">One</a></li>
+                         <li><a href="text/two.xhtml#start">Two</a></li>
+                         <li><a href="text/three.xhtml">Three</a></li>
+                       </ol></nav>
+                     </body></html>"#;
+        let bytes = build_epub(&[("META-INF/container.xml", container),
+                                 ("OEBPS/content.opf", opf),
+                                 ("OEBPS/nav.xhtml", nav),
+                                 ("OEBPS/text/one.xhtml", &chapter("<p>First.</p>")),
+                                 ("OEBPS/text/two.xhtml", &chapter("<p>Second.</p>")),
+                                 ("OEBPS/text/three.xhtml", &chapter("<p>Third.</p>"))]);
+        assert!(is_epub(&bytes));
+        let book = read_epub(&bytes).unwrap();
+        let text: String = book.text_vec.iter().collect();
+        let titles: Vec<&str> = book.chapters.iter().map(|chapter| chapter.title.as_str()).collect();
+        assert_eq!(titles, ["One", "Two", "Three"]);
+        for (chapter, first_word) in book.chapters.iter().zip(["First.", "Second.", "Third."]) {
+            assert!(text[chapter.start_pos..].starts_with(first_word), "{} at {}", first_word, chapter.start_pos);
+        }
+    }
+
+    #[test]
+    fn first_nav_entry_of_a_file_is_its_title() {
+        let nav = r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops"><body>
+                       <nav epub:type="toc"><ol>
+                         <li><a href="text/one.xhtml#part1">Part one</a></li>
+                         <li><a href="text/one.xhtml#part2">Part two</a></li>
+                         <li><a href="text/two.xhtml">Two</a></li>
+                       </ol></nav>
+                     </body></html>"#;
+        let titles = nav_titles(nav, "OEBPS");
+        assert_eq!(titles.len(), 2);
+        assert_eq!(titles["OEBPS/text/one.xhtml"], "Part one");
+        assert_eq!(titles["OEBPS/text/two.xhtml"], "Two");
+    }
+}
@@ -0,0 +1,51 @@
+// The errors of less_fp and their exit codes.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub enum Error {
+    /// Wrong command line arguments, or no text to read.
+    Usage(String),
+    /// The config file can't be read or has wrong values.
+    Config(String),
+    /// The text file doesn't exist.
+    FileNotFound(PathBuf),
+    /// The text can't be read, from the file or from the standard input.
+    Read(String, io::Error),
+    /// The exported text can't be written.
+    Write(String, io::Error),
+    /// The terminal can't be set up or written.
+    Terminal(io::Error),
+}
+
+impl Error {
+    /// The exit code of the process for each class of failure.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => 2,
+            Error::Config(_) => 3,
+            Error::FileNotFound(_) | Error::Read(_, _) | Error::Write(_, _) => 4,
+            Error::Terminal(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(err_str) => write!(f, "{}", err_str),
+            Error::Config(err_str) => write!(f, "{}", err_str),
+            Error::FileNotFound(path) => write!(f, "The text filename '{}' doesn't exist!", path.to_string_lossy()),
+            Error::Read(source, err) => write!(f, "While reading {} ... {}", source, err),
+            Error::Write(target, err) => write!(f, "While writing {} ... {}", target, err),
+            Error::Terminal(err) => write!(f, "In the terminal ... {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Terminal(err)
+    }
+}
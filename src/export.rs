@@ -0,0 +1,317 @@
+// The export of the text with the fixation points, to share it with the
+// readers that don't use a terminal. The text is written as HTML, with the
+// fixation points in <b>, as Markdown, with the fixation points in **bold**,
+// as text with the ANSI escape sequences, ex: for less -R, or as plain text.
+
+use std::io::{self, Write};
+
+use crossterm::style::Stylize;
+
+use crate::encoding::escape_control_char;
+use crate::style::{StyleAt, StyleSpan, TextStyle};
+use less_fp::{fixation_mask, FixationStrategy};
+
+pub const EXPORT_FORMAT_NAMES: [&str; 4] = ["html", "md", "ansi", "text"];
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Html,
+    Markdown,
+    Ansi,
+    Text,
+}
+
+impl ExportFormat {
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "html" => Some(ExportFormat::Html),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "ansi" => Some(ExportFormat::Ansi),
+            "text" | "txt" => Some(ExportFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body { margin: 2em auto; max-width: 42em; padding: 0 1em; line-height: 1.5; }
+.text { white-space: pre-wrap; font-family: Georgia, serif; font-size: 1.1em; }
+.text b { font-weight: 700; }
+</style>
+</head>
+<body>
+<div class="text">"#;
+
+const HTML_TAIL: &str = "</div>\n</body>\n</html>\n";
+
+/// Writes the text in the format, with the fixation points of the strategy,
+/// or without them when it is None, and with the styles of the markup.
+pub fn export(text_vec: &[char], styles: &[StyleSpan], fixation: Option<FixationStrategy>, format: ExportFormat,
+              title: &str, out: & mut dyn Write) -> io::Result<()> {
+    // The chars in bold of the fixation points, as in the pages.
+    let bold_mask = match fixation {
+        Some(fixation) => fixation_mask(text_vec, fixation),
+        None => vec![false; text_vec.len()],
+    };
+    let char_style = |i: usize| -> TextStyle {
+        let mut text_style = styles.style_at(i).copied().unwrap_or_default();
+        text_style.bold = text_style.bold || (!text_style.no_fixation && bold_mask[i]);
+        text_style.no_fixation = false;
+        text_style
+    };
+
+    match format {
+        ExportFormat::Html => {
+                write!(out, "{}", HTML_HEAD.replace("{title}", &escape_html(title)))?;
+                for (start, end, text_style) in style_runs(text_vec, char_style) {
+                    let text: String = text_vec[start..end].iter().map(|c| shown_char(*c)).collect();
+                    let tags: Vec<&str> = [(text_style.bold, "b"), (text_style.italic, "i"), (text_style.underline, "u")]
+                        .iter()
+                        .filter(|(is_set, _tag)| *is_set)
+                        .map(|(_is_set, tag)| *tag)
+                        .collect();
+                    for tag in tags.iter() {
+                        write!(out, "<{}>", tag)?;
+                    }
+                    write!(out, "{}", escape_html(&text))?;
+                    for tag in tags.iter().rev() {
+                        write!(out, "</{}>", tag)?;
+                    }
+                }
+                write!(out, "{}", HTML_TAIL)
+            },
+        ExportFormat::Markdown => {
+                let mut lines_vec: Vec<&[char]> = text_vec.split(|c| *c == '\n').collect();
+                if lines_vec.last().is_some_and(|line| line.is_empty()) {
+                    // The new line at the end of the text.
+                    lines_vec.pop();
+                }
+                let mut line_start = 0_usize;
+                for (line_index, line) in lines_vec.iter().enumerate() {
+                    // The lines of the text stay apart, with a hard line break.
+                    let next_is_text = lines_vec.get(line_index + 1).is_some_and(|next| !next.is_empty());
+                    let hard_break = if next_is_text && !line.is_empty() { "  " } else { "" };
+                    writeln!(out, "{}{}", markdown_line(line, line_start, &char_style), hard_break)?;
+                    line_start += line.len() + 1;
+                }
+                Ok(())
+            },
+        ExportFormat::Ansi => {
+                for (start, end, text_style) in style_runs(text_vec, char_style) {
+                    let text: String = text_vec[start..end].iter().map(|c| shown_char(*c)).collect();
+                    if text_style == TextStyle::default() {
+                        write!(out, "{}", text)?;
+                        continue;
+                    }
+                    let mut content = text.stylize();
+                    if let Some(fg) = text_style.fg {
+                        content = content.with(fg);
+                    }
+                    if let Some(bg) = text_style.bg {
+                        content = content.on(bg);
+                    }
+                    if text_style.bold {
+                        content = content.bold();
+                    }
+                    if text_style.italic {
+                        content = content.italic();
+                    }
+                    if text_style.underline {
+                        content = content.underlined();
+                    }
+                    if text_style.reverse {
+                        content = content.reverse();
+                    }
+                    write!(out, "{}", content)?;
+                }
+                Ok(())
+            },
+        ExportFormat::Text => {
+                let text: String = text_vec.iter().map(|c| shown_char(*c)).collect();
+                write!(out, "{}", text)
+            },
+    }
+}
+
+/// The (start, end, style) of the runs of chars with the same style, the new
+/// lines are runs without style, so the styles don't cross the lines.
+fn style_runs(text_vec: &[char], char_style: impl Fn(usize) -> TextStyle) -> Vec<(usize, usize, TextStyle)> {
+    let mut runs_vec: Vec<(usize, usize, TextStyle)> = Vec::new();
+    for (i, c) in text_vec.iter().enumerate() {
+        let text_style = if *c == '\n' { TextStyle::default() } else { char_style(i) };
+        match runs_vec.last_mut() {
+            Some((_start, end, run_style)) if *run_style == text_style && *c != '\n' && text_vec[*end - 1] != '\n' => {
+                *end = i + 1;
+            },
+            _ => runs_vec.push((i, i + 1, text_style)),
+        }
+    }
+    runs_vec
+}
+
+/// The line in Markdown, the bold chars between "**", the chars of the markup
+/// escaped and the indentation kept with non-breaking spaces.
+fn markdown_line(line: &[char], line_start: usize, char_style: &impl Fn(usize) -> TextStyle) -> String {
+    let mut markdown = String::new();
+    let indent_len = line.iter().take_while(|c| **c == ' ' || **c == '\t').count();
+    for c in line[..indent_len].iter() {
+        markdown.push_str(if *c == '\t' { "&nbsp;&nbsp;&nbsp;&nbsp;" } else { "&nbsp;" });
+    }
+    let rest = &line[indent_len..];
+    // The line would start a heading, a quote, a list or a table.
+    let digits_len = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+    let marker_pos = match rest.first() {
+        Some('#' | '>' | '-' | '+' | '=' | '|') => Some(0),
+        _ if digits_len > 0 && matches!(rest.get(digits_len), Some('.' | ')')) => Some(digits_len),
+        _ => None,
+    };
+
+    let mut in_bold = false;
+    for (i, c) in rest.iter().enumerate() {
+        // The bold doesn't cross the white spaces, "**" next to a space isn't bold in Markdown.
+        let is_bold = !c.is_whitespace() && char_style(line_start + indent_len + i).bold;
+        if is_bold != in_bold {
+            markdown.push_str("**");
+            in_bold = is_bold;
+        }
+        if marker_pos == Some(i) || matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            markdown.push('\\');
+        }
+        markdown.push(shown_char(*c));
+    }
+    if in_bold {
+        markdown.push_str("**");
+    }
+    markdown
+}
+
+/// The control chars, except the tabs and the new lines, are replaced.
+fn shown_char(c: char) -> char {
+    if c != '\t' && c != '\n' && escape_control_char(c).is_some() {
+        '\u{fffd}'
+    } else {
+        c
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ansi::parse_ansi;
+
+    fn export_string(text: &str, styles: &[StyleSpan], format: ExportFormat, title: &str) -> String {
+        let text_vec: Vec<char> = text.chars().collect();
+        let mut out: Vec<u8> = Vec::new();
+        export(&text_vec, styles, Some(FixationStrategy::Half), format, title, & mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// The line in Markdown, with the chars marked by '^' in bold.
+    fn markdown(line: &str, bold: &str) -> String {
+        let line_vec: Vec<char> = line.chars().collect();
+        let bold_vec: Vec<bool> = bold.chars().map(|c| c == '^').chain(std::iter::repeat(false)).take(line_vec.len()).collect();
+        markdown_line(&line_vec, 0, &|i| TextStyle { bold: bold_vec[i], ..TextStyle::default() })
+    }
+
+    /// The text in the tags, or between the markers, ex: "<b>" and "</b>".
+    fn between(text: &str, open: &str, close: &str) -> String {
+        let mut inside = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(open) {
+            let after = &rest[start + open.len()..];
+            let end = after.find(close).unwrap();
+            inside.push_str(&after[..end]);
+            rest = &after[end + close.len()..];
+        }
+        inside
+    }
+
+    #[test]
+    fn markdown_bold_stops_at_the_white_spaces() {
+        assert_eq!(markdown("Reading fast", "^^^^____^^^^"), "**Read**ing **fast**");
+        // A bold run with a space inside is split around it.
+        assert_eq!(markdown("ab cd", "^^^^^"), "**ab** **cd**");
+        assert_eq!(markdown("x", "^"), "**x**");
+    }
+
+    #[test]
+    fn markdown_escapes_the_markup() {
+        assert_eq!(markdown(r"a\b *c* _d_ `e` [f] <g>", ""), r"a\\b \*c\* \_d\_ \`e\` \[f\] \<g>");
+        // The markers at the start of a line.
+        assert_eq!(markdown("# Title", ""), r"\# Title");
+        assert_eq!(markdown("> quote", ""), r"\> quote");
+        assert_eq!(markdown("- item", ""), r"\- item");
+        assert_eq!(markdown("12. item", ""), r"12\. item");
+        assert_eq!(markdown("3) item", ""), r"3\) item");
+        // Not in the middle of a line.
+        assert_eq!(markdown("a - b # c", ""), "a - b # c");
+        assert_eq!(markdown("1984 was", ""), "1984 was");
+    }
+
+    #[test]
+    fn markdown_keeps_the_indentation() {
+        assert_eq!(markdown("  - item", ""), r"&nbsp;&nbsp;\- item");
+        assert_eq!(markdown("\tcode", ""), "&nbsp;&nbsp;&nbsp;&nbsp;code");
+        assert_eq!(markdown("  bold", "  ^^"), "&nbsp;&nbsp;**bo**ld");
+    }
+
+    #[test]
+    fn style_runs_dont_cross_the_new_lines() {
+        let text_vec: Vec<char> = "ab\ncd\n\nef".chars().collect();
+        let bold = TextStyle { bold: true, ..TextStyle::default() };
+        let runs_vec = style_runs(&text_vec, |_i| bold);
+        let plain = TextStyle::default();
+        assert_eq!(runs_vec, [(0, 2, bold), (2, 3, plain), (3, 5, bold), (5, 6, plain), (6, 7, plain), (7, 9, bold)]);
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let html = export_string("a < b && c > d\n", &[], ExportFormat::Html, "Tom & <Jerry>");
+        assert!(html.contains("<title>Tom &amp; &lt;Jerry&gt;</title>"));
+        assert!(html.contains("<b>a</b> &lt; <b>b</b> &amp;&amp; <b>c</b> &gt; <b>d</b>\n"));
+        assert!(html.ends_with(HTML_TAIL));
+    }
+
+    #[test]
+    fn markdown_hard_breaks_between_the_lines() {
+        let md = export_string("one\ntwo\n\nthree\n", &[], ExportFormat::Markdown, "");
+        // The line before the blank line and the last line have no hard break.
+        assert_eq!(md, "**o**ne  \n**t**wo\n\n**thr**ee\n");
+    }
+
+    #[test]
+    fn formats_agree_on_the_bold_chars() {
+        let text = "Price 42. It's 3rd, fine—really.\nNext line\n";
+        let text_vec: Vec<char> = text.chars().collect();
+        let expected: String = fixation_mask(&text_vec, FixationStrategy::Half).iter()
+                                                                               .zip(text_vec.iter())
+                                                                               .filter(|(is_bold, c)| **is_bold && !c.is_whitespace())
+                                                                               .map(|(_is_bold, c)| *c)
+                                                                               .collect();
+        assert_eq!(expected, "Pri42Is3rdfireaNeli");
+
+        let html = export_string(text, &[], ExportFormat::Html, "");
+        assert_eq!(between(&html, "<b>", "</b>"), expected);
+        let md = export_string(text, &[], ExportFormat::Markdown, "");
+        assert_eq!(between(&md, "**", "**").replace('\\', ""), expected);
+        let ansi: Vec<char> = export_string(text, &[], ExportFormat::Ansi, "").chars().collect();
+        let (plain_vec, spans_vec) = parse_ansi(&ansi);
+        assert_eq!(plain_vec, text_vec);
+        let ansi_bold: String = spans_vec.iter()
+                                         .filter(|span| span.style.bold)
+                                         .flat_map(|span| plain_vec[span.start_pos..=span.end_pos].iter())
+                                         .collect();
+        assert_eq!(ansi_bold, expected);
+    }
+}
@@ -0,0 +1,193 @@
+// The words of a text and their fixation points, the chars at the start of
+// each word that are shown in bold.
+
+use serde::Deserialize;
+
+/// How much of the start of a word is in bold.
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FixationStrategy {
+    /// Around the first half of the word.
+    #[default]
+    Half,
+    /// Around the first third of the word.
+    Third,
+    /// Only the first letter of the word.
+    FirstLetter,
+}
+
+impl FixationStrategy {
+    /// Returns (middle_start, middle_end), the bold goes from the start of the word to middle_start.
+    ///
+    /// ```
+    /// use less_fp::FixationStrategy;
+    ///
+    /// // The word "fixation", the chars 0 to 8.
+    /// assert_eq!(FixationStrategy::Half.calc_middle_start_end_point(0, 8), (3, 4));
+    /// assert_eq!(FixationStrategy::Third.calc_middle_start_end_point(0, 8), (2, 3));
+    /// assert_eq!(FixationStrategy::FirstLetter.calc_middle_start_end_point(0, 8), (0, 1));
+    /// ```
+    pub fn calc_middle_start_end_point(&self, word_start: usize, word_end: usize) -> (usize, usize) {
+        match self {
+            FixationStrategy::Half => calc_middle_start_end_point(word_start, word_end),
+            FixationStrategy::Third => {
+                let len = word_end - word_start;
+                let middle_start = word_start + len.div_ceil(3) - 1;
+                (middle_start, middle_start + 1)
+            },
+            FixationStrategy::FirstLetter => (word_start, word_start + 1),
+        }
+    }
+}
+
+/// Returns (middle_start, middle_end), the fixation point around the middle of
+/// the word from word_start to word_end, exclusive.
+pub fn calc_middle_start_end_point(word_start: usize, word_end: usize) -> (usize, usize) {
+    let len = word_end - word_start;    
+    // É Impar?
+    let is_odd = !len.is_multiple_of(2); 
+    let middle_f32 = len as f32 / 2.0;
+    let _exact_value = middle_f32 % 1.0;
+
+    let middle_start;
+    let middle_end;
+    if len >= 5 && is_odd {
+        middle_start = word_start + middle_f32 as usize;
+        middle_end = word_start + middle_f32 as usize + 1;
+    } else if len == 1 {
+        middle_start = word_start;
+        middle_end = word_start;
+    } else {
+        middle_start = word_start + middle_f32 as usize - 1;
+        middle_end = word_start + middle_f32 as usize;
+    }
+
+    (middle_start, middle_end)
+}
+
+/// A word of the text, a run of alphanumeric chars, with its fixation point.
+/// The positions are indices of the chars of the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Word {
+    /// The first char of the word.
+    pub start: usize,
+    /// The char after the word.
+    pub end: usize,
+    /// The last char in bold, or the end of the word when the word starts with a number
+    /// and is all in bold. The chars in bold end at bold_end().
+    pub middle_start: usize,
+    pub middle_end: usize,
+}
+
+impl Word {
+    /// The char after the chars in bold, the words that start with a number are all in bold.
+    pub fn bold_end(&self) -> usize {
+        (self.middle_start + 1).min(self.end)
+    }
+}
+
+/// Find the start and end indices of the words in the text and corrects for a sequence of white spaces or tabs.
+///
+/// ```
+/// use less_fp::{tokenize, FixationStrategy, Word};
+///
+/// let text: Vec<char> = "It's 42.".chars().collect();
+/// let words = tokenize(&text, FixationStrategy::Half);
+/// assert_eq!(words, vec![
+///     Word { start: 0, end: 2, middle_start: 0, middle_end: 1 },
+///     Word { start: 3, end: 4, middle_start: 3, middle_end: 3 },
+///     // The words that start with a number are all in bold.
+///     Word { start: 5, end: 7, middle_start: 7, middle_end: 7 },
+/// ]);
+/// ```
+pub fn tokenize(text: &[char], strategy: FixationStrategy) -> Vec<Word> {
+    let mut words_index: Vec<Word> = Vec::new();
+    let mut flag_inside_word = false;
+    let mut start = 0 ;
+    // The word at the end of the text ends at the end of the text.
+    for i in 0..=text.len() {
+        if text.get(i).is_some_and(|c| c.is_alphanumeric()) {
+            if !flag_inside_word {
+                flag_inside_word = true;
+                start = i;
+            }
+        } else if flag_inside_word {
+            flag_inside_word = false;
+            let end = i;
+            let middle_start;
+            let middle_end;
+            // If word starts with a number, all the word will be bold :-)
+            if text[start].is_numeric() {
+                (middle_start, middle_end) = (end, end);
+            } else {
+                (middle_start, middle_end) = strategy.calc_middle_start_end_point(start, end);
+            }
+            words_index.push(Word {start, end, middle_start, middle_end});
+        }
+    }
+    words_index
+}
+
+/// For each char of the text, if it is in bold.
+pub fn fixation_mask(text: &[char], strategy: FixationStrategy) -> Vec<bool> {
+    let mut mask = vec![false; text.len()];
+    for word in tokenize(text, strategy) {
+        for is_bold in mask[word.start..word.bold_end()].iter_mut() {
+            *is_bold = true;
+        }
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The chars in bold of the text, and a '_' for the others.
+    fn bold_chars(text: &str, strategy: FixationStrategy) -> String {
+        let text_vec: Vec<char> = text.chars().collect();
+        let mask = fixation_mask(&text_vec, strategy);
+        text_vec.iter().zip(mask).map(|(c, is_bold)| if is_bold { *c } else { '_' }).collect()
+    }
+
+    #[test]
+    fn number_words_are_all_in_bold() {
+        let text_vec: Vec<char> = "Price 42. x".chars().collect();
+        let words = tokenize(&text_vec, FixationStrategy::Half);
+        assert_eq!(words[1], Word { start: 6, end: 8, middle_start: 8, middle_end: 8 });
+        assert_eq!(words[1].bold_end(), 8);
+        // The '.' after the number isn't in bold.
+        assert_eq!(bold_chars("Price 42. x", FixationStrategy::Half), "Pri___42__x");
+        assert_eq!(bold_chars("1984, 3rd", FixationStrategy::FirstLetter), "1984__3rd");
+    }
+
+    #[test]
+    fn apostrophes_split_the_words() {
+        let text_vec: Vec<char> = "don't".chars().collect();
+        let words = tokenize(&text_vec, FixationStrategy::Half);
+        assert_eq!(words.iter().map(|word| (word.start, word.end)).collect::<Vec<_>>(), [(0, 3), (4, 5)]);
+        assert_eq!(bold_chars("don't", FixationStrategy::Half), "d___t");
+    }
+
+    #[test]
+    fn punctuation_is_never_in_bold() {
+        assert_eq!(bold_chars("\"Hello,\" said (the) cat...", FixationStrategy::Half),
+                   "_Hel_____sa____t____c_____");
+        assert_eq!(bold_chars("-- ... !?", FixationStrategy::Half), "_________");
+    }
+
+    #[test]
+    fn strategies() {
+        assert_eq!(bold_chars("fixation points", FixationStrategy::Half), "fixa_____poi___");
+        assert_eq!(bold_chars("fixation points", FixationStrategy::Third), "fix______po____");
+        assert_eq!(bold_chars("fixation points", FixationStrategy::FirstLetter), "f________p_____");
+    }
+
+    #[test]
+    fn word_at_the_end_of_the_text() {
+        let text_vec: Vec<char> = "end".chars().collect();
+        assert_eq!(tokenize(&text_vec, FixationStrategy::Half).len(), 1);
+        assert_eq!(bold_chars("", FixationStrategy::Half), "");
+        assert_eq!(bold_chars("a", FixationStrategy::Half), "a");
+    }
+}
@@ -0,0 +1,210 @@
+// The reading guide, a highlight that moves word by word, or row by row, across
+// the page at the speed of the pacing mode, so the eye has a target. It pauses
+// longer after the punctuation, stops at the end of the page, or goes to the
+// next page when the pacing is running.
+
+use std::time::{Duration, Instant};
+
+use crate::pacing::MIN_PAGE_DURATION;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GuideMode {
+    Word,
+    Line,
+}
+
+/// A position of the guide on the page, the chars that are highlighted and
+/// the time to read them, in words.
+pub struct GuideStep {
+    pub start: usize,
+    pub end: usize,
+    weight: u32,
+}
+
+pub struct Guide {
+    pub mode: GuideMode,
+    step: usize,
+    // The page of the steps and when the current step was shown.
+    page_num: usize,
+    step_start: Instant,
+}
+
+impl Guide {
+    pub fn new(mode: GuideMode) -> Self {
+        Guide {
+            mode,
+            step: 0,
+            page_num: usize::MAX,
+            step_start: Instant::now(),
+        }
+    }
+
+    /// The guide starts with the words, then goes by rows, then it is hidden.
+    pub fn next_mode(guide: Option<Guide>) -> Option<Guide> {
+        match guide.map(|guide| guide.mode) {
+            None => Some(Guide::new(GuideMode::Word)),
+            Some(GuideMode::Word) => Some(Guide::new(GuideMode::Line)),
+            Some(GuideMode::Line) => None,
+        }
+    }
+
+    /// The current step, the steps of a page that changed, ex: after a resize, could be fewer.
+    pub fn current_step<'a>(&self, steps: &'a [GuideStep]) -> Option<&'a GuideStep> {
+        steps.get(self.step.min(steps.len().saturating_sub(1)))
+    }
+
+    /// The time of the current step starts again.
+    pub fn restart_step(& mut self) {
+        self.step_start = Instant::now();
+    }
+
+    /// The time left before the next step, or None when the guide stopped at
+    /// the last step of the page. With the auto advance, the time of the last
+    /// step is the time left before the next page. The guide starts again at
+    /// the first step when the page changes.
+    pub fn time_left(& mut self, page_num: usize, steps: &[GuideStep], wpm: u16, auto_advance: bool) -> Option<Duration> {
+        if page_num != self.page_num {
+            self.page_num = page_num;
+            self.step = 0;
+            self.step_start = Instant::now();
+        }
+        if self.step + 1 >= steps.len() && !auto_advance {
+            return None;
+        }
+        let duration = match self.current_step(steps) {
+            Some(step) => Duration::from_secs_f64(step.weight as f64 * 60.0 / wpm as f64),
+            // The pages without words.
+            None => MIN_PAGE_DURATION,
+        };
+        Some((self.step_start + duration).saturating_duration_since(Instant::now()))
+    }
+
+    /// Goes to the next step, returns false at the last step of the page.
+    pub fn next_step(& mut self, num_steps: usize) -> bool {
+        if self.step + 1 < num_steps {
+            self.step += 1;
+            self.step_start = Instant::now();
+            return true;
+        }
+        false
+    }
+
+    /// The status shown on the screen, ex: "word guide 250 wpm", with "▶" when the pages advance.
+    pub fn status(&self, wpm: u16, auto_advance: bool) -> String {
+        let mode_name = match self.mode {
+            GuideMode::Word => "word",
+            GuideMode::Line => "line",
+        };
+        let symbol = if auto_advance { "▶ " } else { "" };
+        format!(" {} guide {}{} wpm ", mode_name, symbol, wpm)
+    }
+}
+
+/// The steps of the guide on the page, from the (start, end) of the words and
+/// the (row_start, row_stop) of the rows, the rows without words are skipped.
+pub fn guide_steps(p_buf: &[char], words: &[(usize, usize)], rows: &[(usize, usize)], mode: GuideMode) -> Vec<GuideStep> {
+    match mode {
+        GuideMode::Word => words.iter()
+                                .map(|(start, end)| GuideStep { start: *start, end: *end, weight: word_weight(p_buf, *end) })
+                                .collect(),
+        GuideMode::Line => rows.iter()
+                               .filter_map(|(row_start, row_stop)| {
+                                   let weight: u32 = words.iter()
+                                                          .filter(|(start, _end)| start >= row_start && start <= row_stop)
+                                                          .map(|(_start, end)| word_weight(p_buf, *end))
+                                                          .sum();
+                                   let end = if p_buf[*row_stop] == '\n' { *row_stop } else { *row_stop + 1 };
+                                   (weight > 0).then_some(GuideStep { start: *row_start, end, weight })
+                               })
+                               .collect(),
+    }
+}
+
+/// The time to read a word, in words, longer before a pause of the punctuation:
+/// twice after a comma and three times at the end of a sentence.
+pub fn word_weight(p_buf: &[char], word_end: usize) -> u32 {
+    // The closing quotes and brackets are skipped, ex: "end." or (end).
+    let mut i = word_end;
+    while i < p_buf.len() && matches!(p_buf[i], '"' | '\'' | '”' | '’' | '»' | ')' | ']') {
+        i += 1;
+    }
+    let followed_by_space = !p_buf.get(i + 1).is_some_and(|c| c.is_alphanumeric());
+    match p_buf.get(i) {
+        Some('.' | '!' | '?' | '…') if followed_by_space => 3,
+        Some(',' | ';' | ':' | '—') if followed_by_space => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{page_rows, TextArea};
+    use less_fp::{tokenize, FixationStrategy};
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    /// The weight of the first word of the text.
+    fn first_word_weight(text: &str) -> u32 {
+        let text_vec = chars(text);
+        let end = text_vec.iter().position(|c| !c.is_alphanumeric()).unwrap_or(text_vec.len());
+        word_weight(&text_vec, end)
+    }
+
+    /// The steps of the guide on the page, as their text and their weight.
+    fn steps(page: &str, width: u16, height: u16, mode: GuideMode) -> Vec<(String, u32)> {
+        let p_buf = chars(page);
+        let words: Vec<(usize, usize)> = tokenize(&p_buf, FixationStrategy::default()).iter()
+                                                                                       .map(|word| (word.start, word.end))
+                                                                                       .collect();
+        let text_area = TextArea { left_column: 0, gutter_width: 0, width, height, word_wrap: true };
+        guide_steps(&p_buf, &words, &page_rows(&p_buf, &text_area), mode).iter()
+                                                                         .map(|step| (p_buf[step.start..step.end].iter().collect(), step.weight))
+                                                                         .collect()
+    }
+
+    #[test]
+    fn weight_of_the_words() {
+        assert_eq!(first_word_weight("word next"), 1);
+        assert_eq!(first_word_weight("word"), 1);
+        assert_eq!(first_word_weight("word, next"), 2);
+        assert_eq!(first_word_weight("word; next"), 2);
+        assert_eq!(first_word_weight("word—next"), 1);
+        assert_eq!(first_word_weight("word. Next"), 3);
+        assert_eq!(first_word_weight("word?"), 3);
+        assert_eq!(first_word_weight("word…\n"), 3);
+        // The closing quotes and brackets are skipped.
+        assert_eq!(first_word_weight("word.\" Next"), 3);
+        assert_eq!(first_word_weight("word), next"), 2);
+        // The punctuation inside a word, ex: a number or an abbreviation, isn't a pause.
+        assert_eq!(first_word_weight("3.14"), 1);
+        assert_eq!(first_word_weight("e.g"), 1);
+    }
+
+    #[test]
+    fn word_steps() {
+        assert_eq!(steps("One, two.\n\nThree", 20, 5, GuideMode::Word),
+                   [("One".to_string(), 2), ("two".to_string(), 3), ("Three".to_string(), 1)]);
+    }
+
+    #[test]
+    fn row_steps_skip_the_rows_without_words() {
+        assert_eq!(steps("One, two.\n\n---\nThree four\n", 20, 5, GuideMode::Line),
+                   [("One, two.".to_string(), 5), ("Three four".to_string(), 2)]);
+    }
+
+    #[test]
+    fn row_step_at_the_last_line_of_the_page() {
+        // The last page of a text without a new line at the end.
+        assert_eq!(steps("one\ntwo three", 20, 2, GuideMode::Line),
+                   [("one".to_string(), 1), ("two three".to_string(), 2)]);
+        // A wrapped row at the end of the page.
+        assert_eq!(steps("one two three four", 8, 3, GuideMode::Line),
+                   [("one two ".to_string(), 2), ("three ".to_string(), 1), ("four".to_string(), 1)]);
+        // The rows after the height of the page aren't in the steps.
+        assert_eq!(steps("one\ntwo\nthree\n", 20, 2, GuideMode::Line),
+                   [("one".to_string(), 1), ("two".to_string(), 1)]);
+    }
+}
@@ -0,0 +1,509 @@
+// The conversion of HTML and XHTML to the text that is shown, for the HTML
+// files and the chapters of the EPUB books. The tags are removed, the block
+// elements are separated by blank lines, the whitespace is collapsed like in
+// a browser, the scripts and styles aren't shown, the list items get bullets
+// or numbers, and the URLs of the links are listed as footnotes at the end.
+
+use crate::string_utils::StringUtils;
+use crate::style::{push_char_style, StyleSpan, TextStyle};
+
+/// The file extensions of the HTML files, that are converted to text.
+pub const HTML_EXTENSIONS: [&str; 3] = ["html", "htm", "xhtml"];
+
+const BLOCK_TAGS: [&str; 22] = [
+    "p", "div", "h1", "h2", "h3", "h4", "h5", "h6", "blockquote", "dl",
+    "table", "section", "article", "header", "footer", "nav", "aside",
+    "figure", "figcaption", "address", "hr", "main",
+];
+const LINE_TAGS: [&str; 4] = ["tr", "dt", "dd", "caption"];
+const HEADING_TAGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+// The contents of these elements aren't shown.
+const HIDDEN_TAGS: [&str; 7] = ["head", "script", "style", "noscript", "template", "svg", "math"];
+
+struct Converter {
+    text_vec: Vec<char>,
+    spans_vec: Vec<StyleSpan>,
+    style_stack: Vec<(String, TextStyle)>,
+    // The name of the hidden element that is being skipped.
+    hidden_tag: Option<String>,
+    pre_depth: usize,
+    // The new line right after the start tag of a pre isn't shown, like in the browsers.
+    skip_pre_newline: bool,
+    pending_space: bool,
+    // The next number of the ordered lists, or None for the bullet lists.
+    list_stack: Vec<Option<u64>>,
+    // The position after the bullet or the number of the last list item.
+    item_text_pos: Option<usize>,
+    // The URLs of the links, numbered in the order they first appear.
+    link_urls: Vec<String>,
+    // The footnote number of the link that is open.
+    open_link: Option<usize>,
+}
+
+/// Returns true if the text starts like an HTML document.
+pub fn is_html(text: &str) -> bool {
+    let start = text.trim_start_matches('\u{feff}')
+                    .trim_start()
+                    .chars()
+                    .take(14)
+                    .collect::<String>()
+                    .to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// Converts the HTML to the text and its style spans.
+pub fn html_to_text(html: &str) -> (Vec<char>, Vec<StyleSpan>) {
+    let mut converter = Converter {
+        text_vec: Vec::with_capacity(html.len() / 2),
+        spans_vec: Vec::new(),
+        style_stack: Vec::new(),
+        hidden_tag: None,
+        pre_depth: 0,
+        skip_pre_newline: false,
+        pending_space: false,
+        list_stack: Vec::new(),
+        item_text_pos: None,
+        link_urls: Vec::new(),
+        open_link: None,
+    };
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            // The doctype, CDATA and processing instructions.
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+            let end = find_tag_end(rest);
+            converter.tag(&rest[1..end]);
+            rest = rest.get(end + 1..).unwrap_or_default();
+        } else {
+            let first_len = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first_len..].find('<').map_or(rest.len(), |end| end + first_len);
+            converter.text(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    converter.push_link_footnotes();
+
+    while converter.text_vec.last() == Some(&'\n') {
+        converter.text_vec.pop();
+    }
+    converter.text_vec.push('\n');
+    let len = converter.text_vec.len();
+    converter.spans_vec.retain(|span| span.start_pos < len);
+    if let Some(last_span) = converter.spans_vec.last_mut() {
+        last_span.end_pos = last_span.end_pos.min(len - 1);
+    }
+    (converter.text_vec, converter.spans_vec)
+}
+
+/// The byte position of the '>' that ends the tag, outside of the quoted attribute values.
+fn find_tag_end(text: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open_quote), _) if c == open_quote => quote = None,
+            (None, '>') => return i,
+            _ => (),
+        }
+    }
+    text.len()
+}
+
+/// The value of the attribute of the tag, ex: the href of "a href=\"x.html\"".
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag.split_once(char::is_whitespace)?.1;
+    loop {
+        rest = rest.trim_start();
+        let name_end = rest.find(|c: char| c == '=' || c == '/' || c.is_whitespace()).unwrap_or(rest.len());
+        if name_end == 0 {
+            return None;
+        }
+        let attr_name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+        let mut value = "";
+        if let Some(value_start) = rest.strip_prefix('=') {
+            let value_start = value_start.trim_start();
+            let value_end = match value_start.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                        let value_end = value_start[1..].find(quote).map_or(value_start.len(), |end| end + 1);
+                        value = &value_start[1..value_end];
+                        value_end + 1
+                    },
+                _ => {
+                        let value_end = value_start.find(char::is_whitespace).unwrap_or(value_start.len());
+                        value = &value_start[..value_end];
+                        value_end
+                    },
+            };
+            rest = value_start.get(value_end..).unwrap_or_default();
+        }
+        if attr_name.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value));
+        }
+    }
+}
+
+/// The URLs that point outside of the document, ex: "https://example.com".
+fn is_external_url(url: &str) -> bool {
+    url.contains("://") || url.starts_with("mailto:")
+}
+
+impl Converter {
+    /// Processes a tag, ex: "p class=\"x\"", "/p" or "br/".
+    fn tag(& mut self, tag: &str) {
+        let is_end_tag = tag.starts_with('/');
+        let is_empty_tag = tag.ends_with('/');
+        let name: String = tag.trim_start_matches('/')
+                              .chars()
+                              .take_while(|c| c.is_ascii_alphanumeric() || *c == ':')
+                              .collect::<String>()
+                              .to_ascii_lowercase();
+        // Without the namespace prefix of XHTML, ex: "html:p".
+        let name = name.rsplit(':').next().unwrap_or_default().to_string();
+        self.skip_pre_newline = false;
+
+        if let Some(hidden_tag) = &self.hidden_tag {
+            if is_end_tag && *hidden_tag == name {
+                self.hidden_tag = None;
+            }
+            return;
+        }
+        if HIDDEN_TAGS.contains(&name.as_str()) && !is_end_tag && !is_empty_tag {
+            self.hidden_tag = Some(name);
+            return;
+        }
+
+        match name.as_str() {
+            "br" => self.push_char('\n'),
+            "pre" => {
+                    self.end_block();
+                    if is_end_tag {
+                        self.pre_depth = self.pre_depth.saturating_sub(1);
+                    } else {
+                        self.pre_depth += 1;
+                        self.skip_pre_newline = !is_empty_tag;
+                    }
+                },
+            "ul" | "ol" => {
+                    if is_end_tag {
+                        self.list_stack.pop();
+                    } else {
+                        let first_number = match name.as_str() {
+                            "ol" => Some(attribute(tag, "start").and_then(|start| start.trim().parse().ok()).unwrap_or(1)),
+                            _ => None,
+                        };
+                        self.list_stack.push(first_number);
+                    }
+                    // The nested lists are inside the list items, without blank lines.
+                    if self.list_stack.is_empty() || (!is_end_tag && self.list_stack.len() == 1) {
+                        self.end_block();
+                    } else {
+                        self.end_line();
+                    }
+                },
+            "li" => {
+                    self.end_line();
+                    if !is_end_tag {
+                        let indent = "  ".repeat(self.list_stack.len().saturating_sub(1));
+                        let marker = match self.list_stack.last_mut() {
+                            Some(Some(number)) => {
+                                    *number += 1;
+                                    format!("{}. ", *number - 1)
+                                },
+                            _ => "• ".to_string(),
+                        };
+                        self.text_vec.extend(format!("{}{}", indent, marker).chars());
+                        self.item_text_pos = Some(self.text_vec.len());
+                    }
+                },
+            "td" | "th" if !is_end_tag && !self.at_line_start() => {
+                    // The cells of a row are separated by a space.
+                    self.pending_space = true;
+                },
+            "img" => {
+                    if let Some(alt) = attribute(tag, "alt").filter(|alt| !alt.trim().is_empty()) {
+                        self.text(&format!("[image: {}]", alt.trim()));
+                    }
+                },
+            "a" => {
+                    if is_end_tag {
+                        if let Some(link_num) = self.open_link.take() {
+                            self.pop_style("a");
+                            self.push_styled(&format!("[{}]", link_num), TextStyle { no_fixation: true, ..TextStyle::default() });
+                        }
+                    } else if let Some(url) = attribute(tag, "href").filter(|url| is_external_url(url)) {
+                        // The links inside the document, or to the other chapters of a book, don't get a footnote.
+                        let link_index = match self.link_urls.iter().position(|link_url| *link_url == url) {
+                            Some(link_index) => link_index,
+                            None => {
+                                    self.link_urls.push(url);
+                                    self.link_urls.len() - 1
+                                },
+                        };
+                        self.open_link = Some(link_index + 1);
+                        self.push_style("a", |style| style.underline = true);
+                    }
+                },
+            _ if BLOCK_TAGS.contains(&name.as_str()) => self.end_block(),
+            _ if LINE_TAGS.contains(&name.as_str()) => self.end_line(),
+            _ => (),
+        }
+
+        let style_change: Option<fn(& mut TextStyle)> = match name.as_str() {
+            "b" | "strong" => Some(|style| style.bold = true),
+            "i" | "em" | "cite" => Some(|style| style.italic = true),
+            "u" | "ins" => Some(|style| style.underline = true),
+            "code" | "kbd" | "samp" | "tt" => Some(|style| style.no_fixation = true),
+            _ if HEADING_TAGS.contains(&name.as_str()) => Some(|style| style.bold = true),
+            _ => None,
+        };
+        if let Some(change) = style_change {
+            if is_end_tag {
+                self.pop_style(&name);
+            } else if !is_empty_tag {
+                self.push_style(&name, change);
+            }
+        }
+    }
+
+    /// Processes the text between the tags.
+    fn text(& mut self, text: &str) {
+        self.push_text(&decode_entities(text));
+    }
+
+    /// Pushes the text, that is already decoded, with the white space collapsed outside of the pre.
+    fn push_text(& mut self, text: &str) {
+        if self.hidden_tag.is_some() {
+            return;
+        }
+        for c in text.get_vec_chars() {
+            if self.pre_depth > 0 {
+                if self.skip_pre_newline {
+                    // A "\r\n" is one new line.
+                    self.skip_pre_newline = c == '\r';
+                    if c == '\n' || c == '\r' {
+                        continue;
+                    }
+                }
+                self.push_char(c);
+            } else if c.is_whitespace() && c != '\u{a0}' {
+                if !self.at_line_start() && self.item_text_pos != Some(self.text_vec.len()) {
+                    self.pending_space = true;
+                }
+            } else {
+                if self.pending_space {
+                    self.pending_space = false;
+                    self.push_char(' ');
+                }
+                self.push_char(if c == '\u{a0}' { ' ' } else { c });
+            }
+        }
+    }
+
+    /// The list of the URLs of the links, after the text.
+    fn push_link_footnotes(& mut self) {
+        if self.link_urls.is_empty() {
+            return;
+        }
+        self.style_stack.clear();
+        self.end_block();
+        self.push_styled("Links", TextStyle { bold: true, ..TextStyle::default() });
+        self.end_block();
+        for (link_index, url) in std::mem::take(& mut self.link_urls).iter().enumerate() {
+            self.push_styled(&format!("[{}] {}", link_index + 1, url), TextStyle { no_fixation: true, ..TextStyle::default() });
+            self.end_line();
+        }
+    }
+
+    fn current_style(&self) -> TextStyle {
+        self.style_stack.last().map(|(_tag_name, style)| *style).unwrap_or_default()
+    }
+
+    /// Pushes a new style, that is the current style changed by the function.
+    fn push_style(& mut self, tag_name: &str, change: fn(& mut TextStyle)) {
+        let mut style = self.current_style();
+        change(& mut style);
+        self.style_stack.push((tag_name.to_string(), style));
+    }
+
+    /// Closes the element and the elements inside it that weren't closed.
+    fn pop_style(& mut self, tag_name: &str) {
+        if let Some(index) = self.style_stack.iter().rposition(|(name, _style)| name == tag_name) {
+            self.style_stack.truncate(index);
+        }
+    }
+
+    /// Pushes the text with the style, ex: the footnote number of a link. The
+    /// text isn't decoded again, ex: the URL of "&amp;lt;" stays "&lt;".
+    fn push_styled(& mut self, text: &str, style: TextStyle) {
+        self.style_stack.push((String::new(), style));
+        self.push_text(text);
+        self.style_stack.pop();
+    }
+
+    fn at_line_start(&self) -> bool {
+        matches!(self.text_vec.last(), None | Some('\n'))
+    }
+
+    fn push_char(& mut self, c: char) {
+        let style = self.current_style();
+        push_char_style(& mut self.spans_vec, self.text_vec.len(), style);
+        self.text_vec.push(c);
+    }
+
+    fn end_line(& mut self) {
+        self.pending_space = false;
+        if !self.at_line_start() {
+            self.text_vec.push('\n');
+        }
+    }
+
+    fn end_block(& mut self) {
+        self.end_line();
+        if !self.text_vec.is_empty() && !self.text_vec.ends_with(&['\n', '\n']) {
+            self.text_vec.push('\n');
+        }
+    }
+}
+
+/// Decodes the character references, ex: "&amp;", "&eacute;", "&#233;" or "&#xE9;".
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest.find(';')
+                            .filter(|end| *end <= 32)
+                            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end)));
+        match reference {
+            Some((c, end)) => {
+                    decoded.push(c);
+                    rest = &rest[end + 1..];
+                },
+            None => {
+                    decoded.push('&');
+                    rest = &rest[1..];
+                },
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// The names of the Latin-1 characters, from U+00A0 to U+00FF.
+const LATIN_1_ENTITIES: [&str; 96] = [
+    "nbsp", "iexcl", "cent", "pound", "curren", "yen", "brvbar", "sect",
+    "uml", "copy", "ordf", "laquo", "not", "shy", "reg", "macr",
+    "deg", "plusmn", "sup2", "sup3", "acute", "micro", "para", "middot",
+    "cedil", "sup1", "ordm", "raquo", "frac14", "frac12", "frac34", "iquest",
+    "Agrave", "Aacute", "Acirc", "Atilde", "Auml", "Aring", "AElig", "Ccedil",
+    "Egrave", "Eacute", "Ecirc", "Euml", "Igrave", "Iacute", "Icirc", "Iuml",
+    "ETH", "Ntilde", "Ograve", "Oacute", "Ocirc", "Otilde", "Ouml", "times",
+    "Oslash", "Ugrave", "Uacute", "Ucirc", "Uuml", "Yacute", "THORN", "szlig",
+    "agrave", "aacute", "acirc", "atilde", "auml", "aring", "aelig", "ccedil",
+    "egrave", "eacute", "ecirc", "euml", "igrave", "iacute", "icirc", "iuml",
+    "eth", "ntilde", "ograve", "oacute", "ocirc", "otilde", "ouml", "divide",
+    "oslash", "ugrave", "uacute", "ucirc", "uuml", "yacute", "thorn", "yuml",
+];
+
+/// The names of the other common characters.
+const OTHER_ENTITIES: [(&str, char); 34] = [
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''),
+    ("OElig", 'Œ'), ("oelig", 'œ'), ("Scaron", 'Š'), ("scaron", 'š'), ("Yuml", 'Ÿ'),
+    ("fnof", 'ƒ'), ("circ", 'ˆ'), ("tilde", '˜'),
+    ("ensp", '\u{2002}'), ("emsp", '\u{2003}'), ("thinsp", '\u{2009}'),
+    ("zwnj", '\u{200c}'), ("zwj", '\u{200d}'),
+    ("ndash", '–'), ("mdash", '—'), ("lsquo", '‘'), ("rsquo", '’'), ("sbquo", '‚'),
+    ("ldquo", '“'), ("rdquo", '”'), ("bdquo", '„'), ("dagger", '†'), ("Dagger", '‡'),
+    ("bull", '•'), ("hellip", '…'), ("permil", '‰'), ("lsaquo", '‹'), ("rsaquo", '›'),
+    ("euro", '€'),
+];
+
+/// Decodes the name of a character reference, without the '&' and the ';'.
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse::<u32>().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    if let Some(index) = LATIN_1_ENTITIES.iter().position(|entity| *entity == name) {
+        return char::from_u32(0xa0 + index as u32);
+    }
+    OTHER_ENTITIES.iter()
+                  .find(|(entity, _c)| *entity == name)
+                  .map(|(_entity, c)| *c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        html_to_text(html).0.iter().collect()
+    }
+
+    #[test]
+    fn scripts_and_styles_are_removed() {
+        let html = "<html><head><title>T</title><style>p { color: red; }</style></head>\
+                    <body><p>Before</p><script>if (a < b) { x(); }</script><p>After</p></body></html>";
+        assert_eq!(convert(html), "Before\n\nAfter\n");
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(convert("<p>caf&eacute; &amp; cr&#232;me &#x2014; &lt;b&gt;&nbsp;&bogus; AT&T</p>"),
+                   "café & crème — <b> &bogus; AT&T\n");
+    }
+
+    #[test]
+    fn nested_lists_with_numbering() {
+        let html = "<ol start=\"3\"><li>Three<ul><li>Bullet</li></ul></li>\
+                    <li>Four<ol><li>Sub one</li><li>Sub two</li></ol></li></ol><p>After</p>";
+        assert_eq!(convert(html), "3. Three\n  • Bullet\n4. Four\n  1. Sub one\n  2. Sub two\n\nAfter\n");
+    }
+
+    #[test]
+    fn link_footnotes() {
+        let html = "<p><a href=\"https://a.org\">A</a>, <a href=\"#note\">note</a>, \
+                    <a href=\"https://b.org\">B</a> and <a href=\"https://a.org\">A again</a>.</p>";
+        assert_eq!(convert(html), "A[1], note, B[2] and A again[1].\n\nLinks\n\n[1] https://a.org\n[2] https://b.org\n");
+    }
+
+    #[test]
+    fn pre_keeps_the_whitespace() {
+        assert_eq!(convert("<p>Code:</p><pre>fn main() {\n    x  =  1;\n}</pre><p>End</p>"),
+                   "Code:\n\nfn main() {\n    x  =  1;\n}\n\nEnd\n");
+    }
+
+    #[test]
+    fn unterminated_tag_or_comment_at_the_end() {
+        assert_eq!(convert("<p>Text</p><!-- a comment"), "Text\n");
+        assert_eq!(convert("<p>Text</p><a href=\"x"), "Text\n");
+        assert_eq!(convert("<p>Text <"), "Text <\n");
+    }
+
+    #[test]
+    fn footnote_urls_are_not_decoded_twice() {
+        let html = "<p><a href=\"https://a.org/?q=&amp;lt;b&amp;gt;&amp;x=1\">A</a></p>";
+        assert_eq!(convert(html), "A[1]\n\nLinks\n\n[1] https://a.org/?q=&lt;b&gt;&x=1\n");
+    }
+
+    #[test]
+    fn new_line_after_the_pre_start_tag_is_skipped() {
+        assert_eq!(convert("<pre>\nfirst\n  second\n</pre>"), "first\n  second\n");
+        assert_eq!(convert("<pre>\r\n\nafter a blank line</pre>"), "\nafter a blank line\n");
+        // Only the new line right after the tag.
+        assert_eq!(convert("<pre>x\n\ny</pre>"), "x\n\ny\n");
+        assert_eq!(convert("<pre><code>\nfn main() {}</code></pre>"), "\nfn main() {}\n");
+    }
+}
@@ -0,0 +1,175 @@
+// Preprocessing of the input before it is decoded to text: an input
+// preprocessor command, like LESSOPEN in less, and the transparent
+// decompression of gzip, bzip2, xz and zstd, detected by the magic bytes.
+
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+
+pub const ENV_PREPROCESSOR: &str = "LESS_FP_OPEN";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression format by the magic bytes at the start of the data.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+/// Decompresses the data if it is compressed, otherwise returns it unchanged.
+pub fn decompress(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    let compression = match Compression::detect(&bytes) {
+        Some(compression) => compression,
+        None => return Ok(bytes),
+    };
+    let mut output: Vec<u8> = Vec::new();
+    match compression {
+        Compression::Gzip => {
+            MultiGzDecoder::new(&bytes[..]).read_to_end(& mut output)?;
+        },
+        Compression::Bzip2 => {
+            MultiBzDecoder::new(&bytes[..]).read_to_end(& mut output)?;
+        },
+        Compression::Xz => {
+            lzma_rs::xz_decompress(& mut &bytes[..], & mut output)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        },
+        Compression::Zstd => {
+            StreamingDecoder::new(&bytes[..])
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+                .read_to_end(& mut output)?;
+        },
+    }
+    Ok(output)
+}
+
+/// Runs the input preprocessor command, where %s is replaced by the file path,
+/// and returns what it writes to the standard output. Like the "|command %s"
+/// form of LESSOPEN, the leading '|' is optional. When the command fails or
+/// writes nothing, returns None and the file is read as is.
+pub fn run_preprocessor(command: &str, file: &Path) -> io::Result<Option<Vec<u8>>> {
+    let command = command.trim().trim_start_matches('|');
+    let quoted_path = format!("'{}'", file.to_string_lossy().replace('\'', "'\\''"));
+    let command_line = if command.contains("%s") {
+        command.replace("%s", &quoted_path)
+    } else {
+        format!("{} {}", command, quoted_path)
+    };
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(&command_line)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TEXT: &[u8] = "The quick brown fox jumps over the lazy dog.\nÉ pão.\n".as_bytes();
+
+    #[test]
+    fn plain_data_is_unchanged() {
+        assert_eq!(Compression::detect(TEXT), None);
+        assert_eq!(decompress(TEXT.to_vec()).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn gzip_round_trip() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(TEXT).unwrap();
+        let bytes = encoder.finish().unwrap();
+        assert_eq!(Compression::detect(&bytes), Some(Compression::Gzip));
+        assert_eq!(decompress(bytes).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn bzip2_round_trip() {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(TEXT).unwrap();
+        let bytes = encoder.finish().unwrap();
+        assert_eq!(Compression::detect(&bytes), Some(Compression::Bzip2));
+        assert_eq!(decompress(bytes).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn xz_round_trip() {
+        let mut bytes: Vec<u8> = Vec::new();
+        lzma_rs::xz_compress(& mut &TEXT[..], & mut bytes).unwrap();
+        assert_eq!(Compression::detect(&bytes), Some(Compression::Xz));
+        assert_eq!(decompress(bytes).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn zstd_round_trip() {
+        let bytes = ruzstd::encoding::compress_to_vec(TEXT, ruzstd::encoding::CompressionLevel::Fastest);
+        assert_eq!(Compression::detect(&bytes), Some(Compression::Zstd));
+        assert_eq!(decompress(bytes).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn corrupt_streams_are_errors() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(TEXT).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let streams = [
+            gzip[..gzip.len() / 2].to_vec(),
+            b"BZh9 not bzip2 data".to_vec(),
+            vec![0xfd, b'7', b'z', b'X', b'Z', 0x00, 1, 2, 3],
+            vec![0x28, 0xb5, 0x2f, 0xfd, 0xff, 0xff],
+        ];
+        for bytes in streams {
+            assert!(decompress(bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn preprocessor_output_replaces_the_file() {
+        let path = Path::new("it's a file.txt");
+        assert_eq!(run_preprocessor("|echo %s", path).unwrap(), Some(b"it's a file.txt\n".to_vec()));
+        assert_eq!(run_preprocessor("echo", path).unwrap(), Some(b"it's a file.txt\n".to_vec()));
+    }
+
+    #[test]
+    fn failed_or_silent_preprocessor_reads_the_file() {
+        let path = Path::new("file.txt");
+        assert_eq!(run_preprocessor("false", path).unwrap(), None);
+        assert_eq!(run_preprocessor("true %s", path).unwrap(), None);
+    }
+}
@@ -0,0 +1,205 @@
+// The key bindings of the actions, the defaults can be remapped in the config file.
+
+use std::collections::BTreeMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::de::{value, IntoDeserializer};
+use serde::Deserialize;
+
+/// The actions that can be bound to keys.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    PrevPage,
+    NextPage,
+    Search,
+    PrevMatch,
+    NextMatch,
+    NarrowColumn,
+    WidenColumn,
+    ToggleCenter,
+    ToggleLineNumbers,
+    TableOfContents,
+    SetMark,
+    GoToMark,
+    TogglePacing,
+    Faster,
+    Slower,
+    ToggleGuide,
+    ToggleRsvp,
+    ToggleFixation,
+    Statistics,
+}
+
+/// One key or a list of keys, ex: "a" or ["a", "PageDown"].
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+pub struct KeyMap {
+    bindings: Vec<(KeyEvent, Action)>,
+}
+
+const DEFAULT_BINDINGS: [(&str, Action); 20] = [
+    ("Esc", Action::Quit),
+    ("q",   Action::PrevPage),
+    ("a",   Action::NextPage),
+    ("/",   Action::Search),
+    ("p",   Action::PrevMatch),
+    ("n",   Action::NextMatch),
+    ("[",   Action::NarrowColumn),
+    ("]",   Action::WidenColumn),
+    ("c",   Action::ToggleCenter),
+    ("l",   Action::ToggleLineNumbers),
+    ("t",   Action::TableOfContents),
+    ("m",   Action::SetMark),
+    ("'",   Action::GoToMark),
+    ("Space", Action::TogglePacing),
+    ("+",   Action::Faster),
+    ("-",   Action::Slower),
+    ("g",   Action::ToggleGuide),
+    ("r",   Action::ToggleRsvp),
+    ("f",   Action::ToggleFixation),
+    ("=",   Action::Statistics),
+];
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS.iter()
+                                       .map(|(key, action)| (parse_key(key).unwrap(), *action))
+                                       .collect();
+        KeyMap { bindings }
+    }
+}
+
+impl KeyMap {
+    /// The default key bindings, where the actions in the remap have their keys replaced.
+    /// A remapped key is removed from the action that it was bound to.
+    pub fn with_remap(remap: &BTreeMap<String, KeySpec>) -> std::result::Result<Self, String> {
+        let mut keymap = KeyMap::default();
+        for (action_name, key_spec) in remap {
+            let action = &Action::deserialize(action_name.as_str().into_deserializer())
+                .map_err(|err: value::Error| err.to_string())?;
+            let keys = match key_spec {
+                KeySpec::One(key) => vec![key.clone()],
+                KeySpec::Many(keys) => keys.clone(),
+            };
+            keymap.bindings.retain(|(_key, bound_action)| bound_action != action);
+            for key in keys {
+                let key_event = parse_key(&key)
+                    .ok_or(format!("Unknown key '{}' for action {:?}", key, action))?;
+                keymap.bindings.retain(|(bound_key, _action)| *bound_key != key_event);
+                keymap.bindings.push((key_event, *action));
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Returns the action bound to the key of the event.
+    pub fn action(&self, event: &Event) -> Option<Action> {
+        if let Event::Key(key_event) = event {
+            let mut modifiers = key_event.modifiers;
+            if let KeyCode::Char(_) = key_event.code {
+                // The shift is already in the char, ex: 'G'.
+                modifiers.remove(KeyModifiers::SHIFT);
+            }
+            for (key, action) in self.bindings.iter() {
+                if key.code == key_event.code && key.modifiers == modifiers {
+                    return Some(*action);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parses a key name, ex: "a", "G", "Esc", "PageDown", "Space" or "Ctrl-f".
+pub fn parse_key(key: &str) -> Option<KeyEvent> {
+    let (modifiers, name) = match key.strip_prefix("Ctrl-") {
+        Some(name) => (KeyModifiers::CONTROL, name),
+        None => (KeyModifiers::NONE, key),
+    };
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name {
+            "Esc" => KeyCode::Esc,
+            "Enter" => KeyCode::Enter,
+            "Space" => KeyCode::Char(' '),
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            _ => return None,
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_event(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn remap(config: &str) -> std::result::Result<KeyMap, String> {
+        KeyMap::with_remap(&toml::from_str(config).unwrap())
+    }
+
+    #[test]
+    fn parse_key_names() {
+        assert_eq!(parse_key("a"), Some(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)));
+        assert_eq!(parse_key("G"), Some(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE)));
+        assert_eq!(parse_key("Space"), Some(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)));
+        assert_eq!(parse_key("PageDown"), Some(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE)));
+        assert_eq!(parse_key("Ctrl-f"), Some(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key("PgDn"), None);
+        assert_eq!(parse_key("Ctrl-"), None);
+        assert_eq!(parse_key(""), None);
+    }
+
+    #[test]
+    fn remap_replaces_the_keys_of_the_action() {
+        let keymap = remap("next_page = [\"j\", \"Ctrl-f\"]").unwrap();
+        assert_eq!(keymap.action(&key_event(KeyCode::Char('a'))), None);
+        assert_eq!(keymap.action(&key_event(KeyCode::Char('j'))), Some(Action::NextPage));
+        let ctrl_f = Event::Key(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL));
+        assert_eq!(keymap.action(&ctrl_f), Some(Action::NextPage));
+        // The other actions keep their default keys.
+        assert_eq!(keymap.action(&key_event(KeyCode::Char('f'))), Some(Action::ToggleFixation));
+    }
+
+    #[test]
+    fn remapped_key_is_removed_from_its_default_action() {
+        let keymap = remap("quit = \"q\"").unwrap();
+        assert_eq!(keymap.action(&key_event(KeyCode::Char('q'))), Some(Action::Quit));
+        assert_eq!(keymap.action(&key_event(KeyCode::Esc)), None);
+    }
+
+    #[test]
+    fn documented_remap_takes_space_from_the_pacing() {
+        let keymap = remap("next_page = [\"a\", \"Space\", \"PageDown\"]\n\
+                            prev_page = [\"q\", \"PageUp\"]").unwrap();
+        assert_eq!(keymap.action(&key_event(KeyCode::Char(' '))), Some(Action::NextPage));
+        assert_eq!(keymap.action(&key_event(KeyCode::PageUp)), Some(Action::PrevPage));
+        assert_eq!(keymap.action(&key_event(KeyCode::Char('+'))), Some(Action::Faster));
+    }
+
+    #[test]
+    fn remap_errors() {
+        assert!(remap("next_page = \"PgDn\"").is_err());
+        assert!(remap("jump = \"j\"").is_err());
+    }
+}
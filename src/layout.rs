@@ -0,0 +1,385 @@
+// The layout of the text on the screen, the reading column, the rows of the
+// text wrapped at its width and the pages of rows of the height of the terminal.
+
+use crate::encoding::escape_control_char;
+use crate::style::{push_char_style, StyleAt, StyleSpan};
+
+pub struct Page {
+    pub global_start_char_pos: usize,
+    pub global_stop_char_pos: usize,
+    // The number of the line, starting at 1, where the page starts.
+    pub start_line_num: usize,
+    // The page starts in the middle of a line that was wrapped.
+    pub starts_mid_line: bool,
+}
+
+pub struct PageVec {
+    curr_page: usize,
+    pages_vec: Vec<Page>,
+}
+
+/// The reading column, the text is limited to a maximum width, with optional
+/// margins and centering inside the terminal.
+pub struct Layout {
+    pub max_width: Option<u16>,
+    pub center: bool,
+    pub margin: u16,
+    pub line_numbers: bool,
+    // The rows break between the words, in the reflow mode.
+    pub word_wrap: bool,
+}
+
+/// The area of the terminal where the text of a page is written.
+/// The line numbers gutter, when present, is at the left of the text.
+pub struct TextArea {
+    pub left_column: u16,
+    pub gutter_width: u16,
+    pub width: u16,
+    pub height: u16,
+    pub word_wrap: bool,
+}
+
+const MIN_COLUMN_WIDTH: u16 = 10;
+const COLUMN_WIDTH_STEP: u16 = 5;
+
+impl Layout {
+    pub fn text_area(&self, term_size: (u16, u16), text_vec: &[char]) -> TextArea {
+        let (term_width, term_height) = term_size;
+        let gutter_width = if self.line_numbers {
+            // The digits of the last line number and a space.
+            let num_lines = text_vec.iter().filter(|c| **c == '\n').count() + 1;
+            num_lines.to_string().len() as u16 + 1
+        } else {
+            0
+        };
+        let available_width = term_width.saturating_sub(self.margin.saturating_mul(2).saturating_add(gutter_width)).max(1);
+        let width = match self.max_width {
+            Some(max_width) => max_width.clamp(1, available_width),
+            None => available_width,
+        };
+        let left_column = if self.center {
+            term_width.saturating_sub(gutter_width.saturating_add(width)) / 2
+        } else {
+            self.margin.min(term_width.saturating_sub(gutter_width.saturating_add(width)))
+        };
+        TextArea {
+            left_column,
+            gutter_width,
+            width,
+            height: term_height.max(1),
+            word_wrap: self.word_wrap,
+        }
+    }
+
+    pub fn narrow(& mut self, text_area: &TextArea) {
+        let width = text_area.width.saturating_sub(COLUMN_WIDTH_STEP).max(MIN_COLUMN_WIDTH);
+        self.max_width = Some(width.min(text_area.width));
+    }
+
+    pub fn widen(& mut self, text_area: &TextArea, term_size: (u16, u16)) {
+        let width = text_area.width.saturating_add(COLUMN_WIDTH_STEP);
+        if width.saturating_add(text_area.gutter_width).saturating_add(self.margin.saturating_mul(2)) >= term_size.0 {
+            // The column fills all the terminal.
+            self.max_width = None;
+        } else {
+            self.max_width = Some(width);
+        }
+    }
+}
+
+const TAB_WIDTH: u16 = 8;
+
+/// The number of columns that a char takes on the screen, at the column of the row.
+/// The tabs go to the next tab stop and the control chars are shown escaped, ex: ^A.
+pub fn char_cells(c: char, column: u16, width: u16) -> u16 {
+    match c as u32 {
+        0x09 => (TAB_WIDTH - column % TAB_WIDTH).min(width.saturating_sub(column)).max(1),
+        0x00..=0x1f | 0x7f => 2,
+        0x80..=0x9f => 4,
+        _ => 1,
+    }
+}
+
+/// The chars shown on the screen for a char of the text, at the column of the row,
+/// and if it is a control char shown escaped.
+pub fn shown_chars(c: char, column: u16, width: u16) -> (String, bool) {
+    if c == '\t' {
+        (" ".repeat(char_cells(c, column, width) as usize), false)
+    } else if let Some(escaped) = escape_control_char(c) {
+        (escaped, true)
+    } else {
+        (c.to_string(), false)
+    }
+}
+
+/// Returns the global position of the last char of the row that starts at
+/// row_start, and the global position where the next row starts.
+/// A row ends at a new line or when it fills the width of the text area.
+pub fn next_row(text_vec: &[char], row_start: usize, width: u16, word_wrap: bool) -> (usize, usize) {
+    let mut column = 0_u16;
+    let mut i = row_start;
+    while i < text_vec.len() {
+        if text_vec[i] == '\n' {
+            return (i, i + 1);
+        }
+        let cells = char_cells(text_vec[i], column, width);
+        if column > 0 && column + cells > width {
+            // The escaped char doesn't fit in the rest of the row.
+            return wrap_at_word(text_vec, row_start, i - 1, word_wrap);
+        }
+        column += cells;
+        if column >= width {
+            // A new line right after a full row, doesn't make an empty row.
+            if i + 1 < text_vec.len() && text_vec[i + 1] == '\n' {
+                return (i + 1, i + 2);
+            }
+            return wrap_at_word(text_vec, row_start, i, word_wrap);
+        }
+        i += 1;
+    }
+    (text_vec.len() - 1, text_vec.len())
+}
+
+/// The text with a new line at the end of each row of the width, the rows are
+/// broken like in the pages, and the styles stay with their chars.
+pub fn wrap_rows(text_vec: &[char], styles: &[StyleSpan], width: u16, word_wrap: bool) -> (Vec<char>, Vec<StyleSpan>) {
+    let mut wrapped_vec: Vec<char> = Vec::with_capacity(text_vec.len());
+    let mut wrapped_styles: Vec<StyleSpan> = Vec::new();
+    let mut row_start = 0_usize;
+    while row_start < text_vec.len() {
+        let (row_stop, next_row_start) = next_row(text_vec, row_start, width, word_wrap);
+        // The spaces at the end of a row that was wrapped between the words aren't written.
+        let mut shown_stop = row_stop;
+        while word_wrap && shown_stop > row_start && text_vec[shown_stop] == ' ' {
+            shown_stop -= 1;
+        }
+        for (i, c) in text_vec.iter().enumerate().take(shown_stop + 1).skip(row_start) {
+            if let Some(text_style) = styles.style_at(i) {
+                push_char_style(& mut wrapped_styles, wrapped_vec.len(), *text_style);
+            }
+            wrapped_vec.push(*c);
+        }
+        if text_vec[row_stop] != '\n' {
+            wrapped_vec.push('\n');
+        }
+        row_start = next_row_start;
+    }
+    (wrapped_vec, wrapped_styles)
+}
+
+/// With the word wrap, a full row ends at the last space before the word that
+/// doesn't fit, and the space right after a full row isn't shown.
+fn wrap_at_word(text_vec: &[char], row_start: usize, row_stop: usize, word_wrap: bool) -> (usize, usize) {
+    if !word_wrap {
+        return (row_stop, row_stop + 1);
+    }
+    match text_vec.get(row_stop + 1) {
+        Some(' ') => return (row_stop, row_stop + 2),
+        None => return (row_stop, row_stop + 1),
+        _ => (),
+    }
+    match text_vec[row_start + 1..=row_stop].iter().rposition(|c| *c == ' ') {
+        Some(space_index) => (row_start + 1 + space_index, row_start + 2 + space_index),
+        // A word longer than the row is broken.
+        None => (row_stop, row_stop + 1),
+    }
+}
+
+/// Returns the global position of the first char of the line number, starting
+/// at 1, or of the last line if the text has fewer lines.
+pub fn find_line_start_pos(text_vec: &[char], line_num: usize) -> usize {
+    let mut cur_line_num = 1_usize;
+    let mut line_start_pos = 0_usize;
+    for (i, c) in text_vec.iter().enumerate() {
+        if cur_line_num >= line_num {
+            break;
+        }
+        if *c == '\n' && i + 1 < text_vec.len() {
+            cur_line_num += 1;
+            line_start_pos = i + 1;
+        }
+    }
+    line_start_pos
+}
+
+impl PageVec {
+    pub fn paginate(text_vec: &[char], text_area: &TextArea) -> Self {
+        let curr_page: usize = 0;
+        let mut page = Page {global_start_char_pos: 0, global_stop_char_pos: 0,
+                             start_line_num: 1, starts_mid_line: false};
+        let mut pages_vec: Vec<Page> = Vec::new();
+
+        let mut cur_row = 0_u16;
+        let mut row_start = 0_usize;
+        let mut line_num = 1_usize;
+        let mut mid_line = false;
+
+        // Paginates - Divide the Vec<chars> into the needed pages for the current size.
+        while row_start < text_vec.len() {
+            if cur_row == text_area.height {
+                // The text spills to the next page.
+                // Creates the new page.
+                page.global_stop_char_pos = row_start - 1;
+                pages_vec.push(page);
+                page = Page {global_start_char_pos: row_start, global_stop_char_pos: row_start,
+                             start_line_num: line_num, starts_mid_line: mid_line};
+                cur_row = 0;
+            }
+            let (row_stop, next_row_start) = next_row(text_vec, row_start, text_area.width, text_area.word_wrap);
+            mid_line = text_vec[row_stop] != '\n';
+            if !mid_line {
+                line_num += 1;
+            }
+            row_start = next_row_start;
+            cur_row += 1;
+        }
+
+        // Adds the last page to be paginated, can be the first, if it only has one.
+        page.global_stop_char_pos = text_vec.len().saturating_sub(1);
+        pages_vec.push(page);
+
+        Self {
+            curr_page,
+            pages_vec,
+        }
+    }
+
+    /// Paginates again, after a change in the size of the text area, and keeps
+    /// the page where the text of the current page starts as the current page.
+    pub fn repaginate(& mut self, text_vec: &[char], text_area: &TextArea) {
+        // Get the old text char position.
+        let (_page_num, page) = self.get_curr_page();
+        let cur_start_page_char_pos = page.global_start_char_pos;
+
+        // Do the new pagination.
+        *self = PageVec::paginate(text_vec, text_area);
+
+        // Find the new page inside the new pagination.
+        let target_page = self.find_char_pos_in_pages(cur_start_page_char_pos);
+
+        // Set the found page as the current page.
+        self.set_curr_page_num(target_page);
+    }
+
+    pub fn get_curr_page(&self) -> (usize, &Page) {
+        (self.curr_page, &self.pages_vec[self.curr_page])
+    }
+
+    pub fn set_curr_page_num(& mut self, page_num: usize) -> bool {
+        if page_num < self.pages_vec.len() {
+            self.curr_page = page_num;
+            return true;
+        }
+        false
+    }
+
+    pub fn num_pages(&self) -> usize {
+        self.pages_vec.len()
+    }
+
+    pub fn find_char_pos_in_pages(&self, global_char_pos: usize) -> usize {
+        for (page_num, page) in self.pages_vec.iter().enumerate() {
+            if    global_char_pos >= page.global_start_char_pos
+               && global_char_pos <= page.global_stop_char_pos {
+                   return page_num;
+               }
+        }
+        0_usize
+    }
+
+    pub fn next_page(& mut self) -> bool {
+        if self.curr_page < self.pages_vec.len() - 1 {
+            self.curr_page += 1;
+            return true;
+        }
+        false
+    }
+
+    pub fn prev_page(& mut self) -> bool {
+        if self.curr_page > 0 {
+            self.curr_page -= 1;
+            return true;
+        }
+        false
+    }
+
+}
+
+/// The (row_start, row_stop) of the rows of the page that are shown in the text area.
+pub fn page_rows(p_buf: &[char], text_area: &TextArea) -> Vec<(usize, usize)> {
+    let mut rows_vec: Vec<(usize, usize)> = Vec::new();
+    let mut row_start = 0_usize;
+    while row_start < p_buf.len() && rows_vec.len() < text_area.height as usize {
+        let (row_stop, next_row_start) = next_row(p_buf, row_start, text_area.width, text_area.word_wrap);
+        rows_vec.push((row_start, row_stop));
+        row_start = next_row_start;
+    }
+    rows_vec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_area(width: u16, height: u16, word_wrap: bool) -> TextArea {
+        TextArea { left_column: 0, gutter_width: 0, width, height, word_wrap }
+    }
+
+    /// The (start, stop) of the pages.
+    fn page_bounds(pages_vec: &PageVec) -> Vec<(usize, usize)> {
+        pages_vec.pages_vec.iter().map(|page| (page.global_start_char_pos, page.global_stop_char_pos)).collect()
+    }
+
+    #[test]
+    fn paginate_empty_text() {
+        let pages_vec = PageVec::paginate(&[], &text_area(10, 3, false));
+        assert_eq!(pages_vec.num_pages(), 1);
+    }
+
+    #[test]
+    fn paginate_only_new_lines() {
+        let text_vec: Vec<char> = "\n\n\n\n\n".chars().collect();
+        let pages_vec = PageVec::paginate(&text_vec, &text_area(10, 2, false));
+        assert_eq!(page_bounds(&pages_vec), [(0, 1), (2, 3), (4, 4)]);
+        assert_eq!(pages_vec.pages_vec[2].start_line_num, 5);
+    }
+
+    #[test]
+    fn paginate_long_line() {
+        let text_vec: Vec<char> = "abcdefghij".repeat(3).chars().collect();
+        let pages_vec = PageVec::paginate(&text_vec, &text_area(4, 3, false));
+        assert_eq!(page_bounds(&pages_vec), [(0, 11), (12, 23), (24, 29)]);
+        assert!(pages_vec.pages_vec[1].starts_mid_line);
+        assert_eq!(pages_vec.pages_vec[1].start_line_num, 1);
+    }
+
+    #[test]
+    fn repaginate_keeps_the_page_text() {
+        let text_vec: Vec<char> = "line 1\nline 2\nline 3\nline 4\nline 5\n".chars().collect();
+        let mut pages_vec = PageVec::paginate(&text_vec, &text_area(10, 2, false));
+        pages_vec.next_page();
+        pages_vec.next_page();
+        pages_vec.repaginate(&text_vec, &text_area(10, 3, false));
+        // The page with "line 5" starts at "line 4".
+        let (page_num, page) = pages_vec.get_curr_page();
+        assert_eq!((page_num, page.global_start_char_pos), (1, 21));
+    }
+
+    #[test]
+    fn next_row_wraps_between_the_words() {
+        let text_vec: Vec<char> = "one two three".chars().collect();
+        assert_eq!(next_row(&text_vec, 0, 6, false), (5, 6));
+        assert_eq!(next_row(&text_vec, 0, 6, true), (3, 4));
+        // A word longer than the row is broken.
+        assert_eq!(next_row(&text_vec, 8, 3, true), (10, 11));
+    }
+
+    #[test]
+    fn huge_margin_leaves_one_column() {
+        let mut layout = Layout { max_width: None, center: false, margin: u16::MAX, line_numbers: true, word_wrap: false };
+        let area = layout.text_area((80, 24), &['a']);
+        assert_eq!((area.width, area.left_column), (1, 77));
+        layout.widen(&area, (80, 24));
+        assert_eq!(layout.max_width, None);
+    }
+}
@@ -0,0 +1,24 @@
+// The library of less_fp, the computation of the fixation points without the
+// terminal, for the other tools that show or write texts with fixation points.
+
+//! The fixation points of a text: the words of the text and, for each word,
+//! the chars at its start that are shown in bold, so the eyes fixate on them
+//! and the rest of the word is completed by the brain.
+//!
+//! ```
+//! use less_fp::{fixation_mask, tokenize, FixationStrategy};
+//!
+//! let text: Vec<char> = "Reading faster".chars().collect();
+//! let words = tokenize(&text, FixationStrategy::Half);
+//! assert_eq!(words.len(), 2);
+//! assert_eq!((words[0].start, words[0].end), (0, 7));
+//!
+//! // The bold chars, "Read" of "Reading" and "fas" of "faster".
+//! let mask = fixation_mask(&text, FixationStrategy::Half);
+//! let bold: String = text.iter().zip(mask).filter(|(_c, is_bold)| *is_bold).map(|(c, _is_bold)| *c).collect();
+//! assert_eq!(bold, "Readfas");
+//! ```
+
+pub mod fixation;
+
+pub use fixation::{calc_middle_start_end_point, fixation_mask, tokenize, FixationStrategy, Word};
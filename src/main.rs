@@ -11,7 +11,18 @@
 //              Use '/' to search + Enter key to exit search mode. 
 //              Use 'p' to prev found string.
 //              Use 'n' to next found string.
+//              Use '[' and ']' to narrow or widen the reading column.
+//              Use 'c' to toggle the centering of the reading column.
+//              Use 'l' to show or hide the line numbers.
+//              Use 't' to show the table of contents of a book.
+//              Use 'm' and a letter to set a mark, and ''' and the letter to go to it.
+//              Use Space to start or pause the pacing, '+' and '-' to change its speed.
+//              Use 'g' to show the reading guide by words, by rows, or to hide it.
+//              Use 'r' to show the words one at a time, and again to go back to the pages.
+//              Use 'f' to hide or show the fixation points.
+//              Use '=' to show the reading statistics.
 //              Use mouse or keyboard for terminal resize.
+//              The keys can be remapped in the config file.
 //              I tested it under Linux, maybe it works under Windows. <br>
 //
 // License: MIT Open Source license.
@@ -20,32 +31,81 @@
 //
 
 use clap::Parser;
-use std::path::PathBuf;
+use less_fp::FixationStrategy;
+use std::path::{Path, PathBuf};
+use std::env;
 use std::fs;
+use std::io::{stdin, BufWriter, ErrorKind, IsTerminal, Read, Write};
+use std::process;
 
 mod string_utils;
+mod ansi;
+use ansi::parse_ansi;
+mod backend;
+use backend::{CrosstermTerminal, Terminal};
+mod config;
+mod controller;
+use controller::{Controller, SessionCommand};
+mod encoding;
+mod epub;
+mod export;
+use export::{ExportFormat, EXPORT_FORMAT_NAMES};
+mod error;
+mod guide;
+use error::Error;
+mod html;
+use html::{html_to_text, HTML_EXTENSIONS};
+mod input;
+use input::Compression;
+mod keymap;
+use keymap::KeyMap;
+mod layout;
+use layout::{find_line_start_pos, wrap_rows, Layout};
+mod markdown;
+#[cfg(test)]
+mod memory_terminal;
+use markdown::{render_markdown, MARKDOWN_EXTENSIONS};
+mod pacing;
+use pacing::DEFAULT_WPM;
+mod reflow;
+mod render;
+use render::{print_message, render};
+mod rsvp;
+use reflow::reflow_paragraphs;
+mod search;
+mod settings;
+use settings::Settings;
+mod state;
+mod stats;
+use state::Bookmarks;
+mod style;
+use style::StyleSpan;
+mod theme;
+use theme::{Theme, THEME_NAMES};
+mod toc;
+use toc::Chapter;
+mod tty;
+use tty::TerminalGuard;
+mod viewer;
+use viewer::{Command, Viewer};
+
 use string_utils::{StringUtils /* , StringUtilsVecCharsV2*/ };
 
 use std::io::{stdout /*, Stdout, Write */};
 
-use crossterm::event::poll;
-use crossterm::style::Color;
 use crossterm::{
-    cursor::{position, MoveTo},
-    event::{read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{EnableMouseCapture, Event},
     execute,
-    terminal::{self, disable_raw_mode, enable_raw_mode},
+    terminal::{self, enable_raw_mode},
     Result,
 };
 use crossterm::ExecutableCommand;
-use crossterm::style::{Color::Rgb, Color::*, Colors, Print, SetColors, Stylize};
+use crossterm::style::Print;
 
 use std::time::Duration;
 
 use crate::string_utils::StringUtilsVecCharsV2;
 
-const COLOR_REAL_BLACK: Color = Rgb {r: 0, g: 0, b: 0};
-
 /// This simple program reads a text file, paginate it and shows it with
 /// fixation points in bold. In principal they are supposed to allow you
 /// to read faster.
@@ -56,588 +116,749 @@ const COLOR_REAL_BLACK: Color = Rgb {r: 0, g: 0, b: 0};
 /// Use '/' to search + Enter key to exit search mode. 
 /// Use 'p' to prev found string.
 /// Use 'n' to next found string.
+/// Use '[' and ']' to narrow or widen the reading column.
+/// Use 'c' to toggle the centering of the reading column.
+/// Use 'l' to show or hide the line numbers.
+/// Use 't' to show the table of contents of a book.
+/// Use 'm' and a letter to set a mark, and ''' and the letter to go to it.
+/// Use Space to start or pause the pacing, '+' and '-' to change its speed.
+/// Use 'g' to show the reading guide by words, by rows, or to hide it.
+/// Use 'r' to show the words one at a time, and again to go back to the pages.
+/// Use 'f' to hide or show the fixation points.
+/// Use '=' to show the reading statistics.
 /// Use mouse or keyboard for terminal resize.
+/// The defaults and the keys can be changed in the config file
+/// ~/.config/less_fp/config.toml and the default flags in the LESS_FP
+/// environment variable.
 ///
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, args_override_self = true)]
 struct Args {
     /// Text file
     #[clap(short, long, parse(from_os_str), value_name = "FILE")]
     file: Option<PathBuf>,
 
-    // TODO:
-    // Read arguments from command line : color, page, Search pattern and next and previous.
+    /// Maximum width of the reading column, in chars
+    #[clap(short = 'w', long, value_name = "CHARS")]
+    max_width: Option<u16>,
+
+    /// Center the reading column on the terminal
+    #[clap(short, long)]
+    center: bool,
+
+    /// Left and right margins of the reading column, in chars
+    #[clap(short, long, value_name = "CHARS")]
+    margin: Option<u16>,
+
+    /// Show the line numbers in a gutter at the left of the text. With --reflow, the lines
+    /// are the ones of the reflowed text, a joined paragraph is one line
+    #[clap(short = 'N', long)]
+    line_numbers: bool,
+
+    /// Join the hard-wrapped lines of the paragraphs and wrap the rows between the words
+    #[clap(long)]
+    reflow: bool,
+
+    /// Don't show the fixation points, to compare the reading speed without them
+    #[clap(long)]
+    no_fixation: bool,
+
+    /// Show the colors of the ANSI escape sequences in the text, ex: grep --color=always
+    #[clap(short = 'R', long)]
+    raw_control_chars: bool,
+
+    /// Render the text as Markdown, the default for the .md and .markdown files
+    #[clap(long)]
+    markdown: bool,
+
+    /// Convert the HTML to text, the default for the .html files and for the text that starts like HTML
+    #[clap(long)]
+    html: bool,
+
+    /// Don't use the alternate screen, the last page stays visible on exit, like less -X
+    #[clap(short = 'X', long)]
+    no_init: bool,
+
+    /// Speed of the pacing mode, of the reading guide and of the RSVP view, in words per minute, by default 250
+    #[clap(long, value_name = "WPM")]
+    wpm: Option<u16>,
+
+    /// Start the pacing mode, the pages advance automatically at the speed of --wpm
+    #[clap(long)]
+    auto_advance: bool,
+
+    /// Don't save the reading position on exit and don't open the file at the last one.
+    /// The position and the marks are saved for the text as shown, so they aren't
+    /// used when the file is opened with or without --markdown, --html, --reflow or
+    /// --raw-control-chars
+    #[clap(long)]
+    no_resume: bool,
+
+    /// Write the text with the fixation points as html, md or ansi, or as plain text, and exit
+    #[clap(long, value_name = "FORMAT")]
+    export: Option<String>,
+
+    /// Width of the rows when the text is written with --export or when the output isn't a terminal,
+    /// by default the lines aren't wrapped
+    #[clap(long, value_name = "CHARS")]
+    width: Option<u16>,
+
+    /// Output file of --export, by default the standard output
+    #[clap(short, long, parse(from_os_str), value_name = "FILE", requires = "export")]
+    output: Option<PathBuf>,
+
+    /// Print the reading statistics of the files, or of the file of --file, and exit
+    #[clap(long)]
+    stats: bool,
+
+    /// Print the statistics of --stats in JSON
+    #[clap(long, requires = "stats")]
+    json: bool,
+
+    /// Encoding of the text, ex: utf-8, utf-16le, latin1 or windows-1252, by default it is detected
+    #[clap(short, long, value_name = "ENCODING")]
+    encoding: Option<String>,
+
+    /// Input preprocessor command, %s is the file, like LESSOPEN in less, ex: "pandoc -t plain %s"
+    #[clap(long, value_name = "COMMAND")]
+    preprocessor: Option<String>,
+
+    /// Config file, by default ~/.config/less_fp/config.toml
+    #[clap(long, parse(from_os_str), value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Color theme: green, amber, white or paper
+    #[clap(short, long, visible_alias = "color", value_name = "THEME")]
+    theme: Option<String>,
+
+    /// Open at the page number
+    #[clap(long, value_name = "N")]
+    page: Option<usize>,
+
+    /// Open at the line number, of the reflowed text with --reflow
+    #[clap(long, value_name = "N")]
+    line: Option<usize>,
+
+    /// Open at the first match of the search pattern, with the matches highlighted
+    #[clap(short, long, value_name = "STR")]
+    pattern: Option<String>,
+
+    /// Startup commands, like in less: +G last page, +g first page, +N line N, +Np N percent, +/STR search
+    #[clap(value_name = "+COMMAND")]
+    commands: Vec<String>,
+}
+
+/// The commands that set the position when the text is opened.
+enum StartCommand {
+    FirstPage,
+    LastPage,
+    Page(usize),
+    Line(usize),
+    Percent(usize),
+    Search(String),
+}
+
+impl StartCommand {
+    /// Parses a startup command of the command line, ex: "+G" or "+/Rabbit".
+    fn parse(command: &str) -> Option<Self> {
+        let command = command.strip_prefix('+')?;
+        match command {
+            "G" => Some(StartCommand::LastPage),
+            "g" => Some(StartCommand::FirstPage),
+            _ => {
+                if let Some(pattern) = command.strip_prefix('/') {
+                    Some(StartCommand::Search(pattern.to_string()))
+                } else if let Some(percent) = command.strip_suffix('p') {
+                    percent.parse().ok().map(StartCommand::Percent)
+                } else {
+                    command.parse().ok().map(StartCommand::Line)
+                }
+            },
+        }
+    }
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        process::exit(err.exit_code());
+    }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn run() -> std::result::Result<(), Error> {
+    let args = Args::parse_from(config::args_with_env_flags());
+    if args.stats {
+        stats::print_stats(args.file.as_deref(), args.json);
+        return Ok(());
+    }
+
+    // The command line options override the values of the config file.
+    let config = config::load(args.config.as_deref()).map_err(Error::Config)?;
+    let theme = match args.theme.as_deref().or(config.theme.as_deref()) {
+        Some(theme_name) => Theme::by_name(theme_name).ok_or_else(|| {
+                let err_str = format!("Unknown theme '{}', the themes are: {}", theme_name, THEME_NAMES.join(", "));
+                if args.theme.is_some() { Error::Usage(err_str) } else { Error::Config(err_str) }
+            })?,
+        None => Theme::default(),
+    };
+    let export_format = match args.export.as_deref() {
+        Some(format_name) => Some(ExportFormat::by_name(format_name).ok_or_else(|| {
+                Error::Usage(format!("Unknown export format '{}', the formats are: {}",
+                                     format_name, EXPORT_FORMAT_NAMES.join(", ")))
+            })?),
+        None => None,
+    };
+    let keymap = KeyMap::with_remap(&config.keys)
+        .map_err(|err_str| Error::Config(format!("In the keys of the config file ... {}", err_str)))?;
+    let settings = Settings {
+        theme,
+        fixation: config.fixation,
+        no_fixation: args.no_fixation || config.no_fixation,
+        search_options: config.search,
+        keymap,
+        no_init: args.no_init || config.no_init,
+        resume: !(args.no_resume || config.no_resume),
+        wpm: args.wpm.or(config.wpm).unwrap_or(DEFAULT_WPM),
+        auto_advance: args.auto_advance || config.auto_advance,
+    };
+    let layout = Layout {
+        max_width: args.max_width.or(config.max_width),
+        center: args.center || config.center,
+        margin: args.margin.unwrap_or(config.margin),
+        line_numbers: args.line_numbers || config.line_numbers,
+        word_wrap: args.reflow || config.reflow,
+    };
+
+    // The position where the text is opened, the options and then the startup commands.
+    let mut start_commands: Vec<StartCommand> = Vec::new();
+    if let Some(page_num) = args.page {
+        start_commands.push(StartCommand::Page(page_num));
+    }
+    if let Some(line_num) = args.line {
+        start_commands.push(StartCommand::Line(line_num));
+    }
+    if let Some(pattern) = args.pattern.as_ref() {
+        start_commands.push(StartCommand::Search(pattern.clone()));
+    }
+    for command in args.commands.iter() {
+        match StartCommand::parse(command) {
+            Some(start_command) => start_commands.push(start_command),
+            None => return Err(Error::Usage(format!("Unknown startup command '{}', see option --help .", command))),
+        }
+    }
 
     // Checking the path of the text file.
-    match args.file.as_deref() {
+    let bytes = match args.file.as_deref() {
         Some(file) => {
                 if !file.exists() {
-                    println!("Error: The text filename '{}' doesn't exist!", file.to_string_lossy());
-                    return Ok(());
-                    // TODO: Add proper exit code with error.
+                    return Err(Error::FileNotFound(file.to_path_buf()));
+                }
+                if export_format.is_none() && stdout().is_terminal() {
+                    println!("Text filename: {}", file.to_string_lossy());
                 }
-                println!("Text filename: {}", file.to_string_lossy());
-                match fs::read_to_string(file) {
-                    // And converts the String to a String Vector. 
-                    Ok(text) => {
-                            let mut text_vec = text.get_vec_chars();
-                            text_vec.replace_str_all("\r\n", "\n");
-                            start_text_mode(&text_vec)?;
-                        },
-                    // TODO: Add proper exit code with error.
-                    Err(err_str) => println!("Error: While reading file ... {}", err_str),
+
+                // The input preprocessor, from the option, the environment variable or the config file.
+                let preprocessor = args.preprocessor.clone()
+                                       .or_else(|| env::var(input::ENV_PREPROCESSOR).ok())
+                                       .or_else(|| config.preprocessor.clone());
+                let preprocessed = match preprocessor {
+                    Some(command) => input::run_preprocessor(&command, file)
+                        .map_err(|err| Error::Read(format!("the preprocessor '{}'", command), err))?,
+                    None => None,
+                };
+                match preprocessed {
+                    Some(bytes) => bytes,
+                    None => fs::read(file)
+                        .map_err(|err| Error::Read(format!("file '{}'", file.to_string_lossy()), err))?,
                 }
-                Ok(())
             },
         None => {
-                println!("Please enter a file, see option --help .");
-                Ok(())
+                if stdin().is_terminal() {
+                    return Err(Error::Usage("Please enter a file, see option --help .".to_string()));
+                }
+                // The text is piped to the standard input.
+                let mut bytes = Vec::new();
+                stdin().read_to_end(& mut bytes)
+                    .map_err(|err| Error::Read("standard input".to_string(), err))?;
+                bytes
             },
+    };
+
+    // Decompresses the gzip, bzip2, xz and zstd compressed texts.
+    let bytes = match Compression::detect(&bytes) {
+        Some(compression) => input::decompress(bytes)
+            .map_err(|err| Error::Read(format!("the {} compressed text", compression.name()), err))?,
+        None => bytes,
+    };
+
+    let mut text_vec: Vec<char>;
+    let mut styles_vec: Vec<StyleSpan> = Vec::new();
+    let mut chapters: Vec<Chapter> = Vec::new();
+    let mut start_message: Option<String> = None;
+    if epub::is_epub(&bytes) {
+        // The EPUB book, the chapters of the spine converted to text.
+        let book = epub::read_epub(&bytes).map_err(|err| Error::Read("EPUB book".to_string(), err))?;
+        (text_vec, styles_vec, chapters) = (book.text_vec, book.styles_vec, book.chapters);
+    } else {
+        // Decodes the text, from the encoding of the option or from the detected encoding.
+        let decoded = encoding::decode(&bytes, args.encoding.as_deref()).map_err(Error::Usage)?;
+        let text = decoded.text;
+        if encoding::is_binary(&text) {
+            let message = format!("Warning: The text seems to be a binary file ({}), the control chars are shown escaped.",
+                                  decoded.encoding_name);
+            eprintln!("{}", message);
+            start_message = Some(message);
+        }
+
+        // The Markdown files are rendered and the HTML files are converted to text, with the styles of the markup.
+        let extension = args.file.as_deref()
+            .and_then(|file| file.extension())
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let is_markdown_file = MARKDOWN_EXTENSIONS.contains(&extension.as_str());
+        let is_html_file = HTML_EXTENSIONS.contains(&extension.as_str()) || html::is_html(&text);
+
+        if args.markdown || (is_markdown_file && !args.html) {
+            (text_vec, styles_vec) = render_markdown(&text.replace("\r\n", "\n"));
+        } else if args.html || is_html_file {
+            (text_vec, styles_vec) = html_to_text(&text.replace("\r\n", "\n"));
+        } else {
+            // And converts the String to a String Vector. 
+            text_vec = text.get_vec_chars();
+            text_vec.replace_str_all("\r\n", "\n");
+            if layout.word_wrap {
+                reflow_paragraphs(& mut text_vec);
+            }
+
+            // Removes the escape sequences from the text and keeps their colors.
+            if args.raw_control_chars || config.raw_control_chars {
+                (text_vec, styles_vec) = parse_ansi(&text_vec);
+            }
+        }
+    }
+
+    // The text with the fixation points is written to the output file, or to the standard output, without the pages.
+    // Like less, when the standard output isn't a terminal the whole text is written, with the fixation points in
+    // the escape sequences of the terminal, or as plain text without them.
+    let stream_format = match export_format {
+        Some(export_format) => Some(export_format),
+        None if !stdout().is_terminal() => Some(if settings.no_fixation { ExportFormat::Text } else { ExportFormat::Ansi }),
+        None => None,
+    };
+    if let Some(stream_format) = stream_format {
+        if let Some(width) = args.width {
+            (text_vec, styles_vec) = wrap_rows(&text_vec, &styles_vec, width.max(1), layout.word_wrap);
+        }
+        let fixation = if settings.no_fixation { None } else { Some(settings.fixation) };
+        let title = args.file.as_deref()
+                             .and_then(|file| file.file_name())
+                             .map_or("Standard input".to_string(), |name| name.to_string_lossy().into_owned());
+        return write_text(&text_vec, &styles_vec, fixation, stream_format, &title, args.output.as_deref());
+    }
+
+    // The marks of the file, saved in the state file.
+    let mut bookmarks = Bookmarks::load(state::file_key(args.file.as_deref(), &text_vec));
+
+    start_text_mode(&text_vec, &styles_vec, &chapters, settings, layout, & mut bookmarks,
+                    &start_commands, start_message.as_deref())?;
+    Ok(())
+}
+
+/// Writes the text in the format to the output file, or to the standard output.
+fn write_text(text_vec: &[char], styles: &[StyleSpan], fixation: Option<FixationStrategy>, format: ExportFormat,
+              title: &str, output: Option<&Path>) -> std::result::Result<(), Error> {
+    let (target, result) = match output {
+        Some(output) => {
+                let target = format!("file '{}'", output.to_string_lossy());
+                let result = fs::File::create(output).and_then(|file| {
+                    let mut out = BufWriter::new(file);
+                    export::export(text_vec, styles, fixation, format, title, & mut out)?;
+                    out.flush()
+                });
+                (target, result)
+            },
+        None => {
+                let mut out = BufWriter::new(stdout().lock());
+                let result = export::export(text_vec, styles, fixation, format, title, & mut out)
+                                 .and_then(|()| out.flush());
+                ("standard output".to_string(), result)
+            },
+    };
+    match result {
+        // The reader of the pipe stopped reading, ex: head.
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => result.map_err(|err| Error::Write(target, err)),
     }
 }
 
-fn start_text_mode(text_vec: &Vec<char>) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+fn start_text_mode(text_vec: &[char], styles: &[StyleSpan], chapters: &[Chapter], settings: Settings,
+                   layout: Layout, bookmarks: & mut Bookmarks, start_commands: &[StartCommand],
+                   start_message: Option<&str>) -> Result<()> {
     println!("Quick reading with fixation points.");
 
+    // The terminal is restored when the guard is dropped, at the end, on an
+    // error or on a panic, or by the signal handler.
+    tty::install_panic_hook();
+    tty::install_signal_handler()?;
     enable_raw_mode()?;
+    let _terminal_guard = TerminalGuard;
 
     let mut stdout = stdout();
+    if settings.no_init {
+        // Scrolls the contents of the screen to the scrollback, instead of erasing them.
+        let (_len_col, len_row) = terminal::size()?;
+        execute!(stdout, Print("\r\n".repeat(len_row as usize)))?;
+    } else {
+        tty::enter_alternate_screen()?;
+    }
     execute!(stdout, EnableMouseCapture)?;
 
     stdout.execute(terminal::Clear(terminal::ClearType::All))?;
 
-    let mut pages_vec = PageVec::paginate(text_vec, terminal::size());
-    let (_page_num, page) = pages_vec.get_curr_page();
-    let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-    let search_opt: Option<Search> = None;
-    let search_string = "";
-    print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-        &SearchMode::NotInMode, search_string);
-
-    if let Err(e) = print_events(text_vec, & mut pages_vec) {
-        println!("Error: {:?}\r", e);
-    }
-
-    execute!(stdout, DisableMouseCapture)?;
-
-    disable_raw_mode()
-
-}
-
-struct TextPos {
-    start_pos: usize,
-    end_pos: usize,
+    let mut viewer = Viewer::new(text_vec, styles, settings, layout, terminal::size()?);
+    print_events(& mut CrosstermTerminal, & mut viewer, chapters, bookmarks, start_commands, start_message)
 }
 
-struct Search {
-    curr_pos: usize,
-    text_pos_vec: Vec<TextPos>,
-}
+fn print_events(term: & mut dyn Terminal, viewer: & mut Viewer, chapters: &[Chapter], bookmarks: & mut Bookmarks,
+                start_commands: &[StartCommand], start_message: Option<&str>) -> Result<()> {
+    let text_vec = viewer.text_vec;
+    // A key read after a resize, when the resize events were flushed.
+    let mut pending_event: Option<Event> = None;
 
-impl Search {
-    fn find(global_text: &Vec<char>, search_string: &str) -> Option<Self> {
-        let ocurrencies = global_text.find_str_all(search_string);
-        if ocurrencies.is_empty() {
-            return None;
-        }
-        let mut text_pos_vec: Vec<TextPos> = Vec::new();
-        for start_pos in ocurrencies {
-            text_pos_vec.push(
-                TextPos { start_pos, end_pos: start_pos + search_string.len() - 1});
-        }
-        Some(
-            Search {
-                curr_pos: 0,
-                text_pos_vec
-                }
-            )
+    // Goes to the last reading position, or to the start position, and shows the first page.
+    if let (true, true, Some(last_pos)) = (viewer.settings.resume, start_commands.is_empty(), bookmarks.file_state.last_pos) {
+        viewer.apply(Command::GoToPos(last_pos));
     }
-
-    fn get_curr_pos(&self) -> (usize, &TextPos) {
-        (self.curr_pos, &self.text_pos_vec[self.curr_pos])
+    for start_command in start_commands {
+        let command = match start_command {
+            StartCommand::FirstPage => Command::FirstPage,
+            StartCommand::LastPage => Command::LastPage,
+            StartCommand::Page(page_num) => Command::GoToPage(*page_num),
+            StartCommand::Line(line_num) => Command::GoToPos(find_line_start_pos(text_vec, *line_num)),
+            StartCommand::Percent(percent) => Command::GoToPos(text_vec.len() * (*percent).min(100) / 100),
+            StartCommand::Search(pattern) => Command::Search(pattern.clone()),
+        };
+        viewer.apply(command);
     }
-
-    fn next_pos(& mut self) -> bool {
-        if self.curr_pos < self.text_pos_vec.len() - 1 {
-            self.curr_pos += 1;
-            return true;
-        }
-        false
+    let mut controller = Controller::new(viewer, chapters, bookmarks);
+    render(term, viewer)?;
+    if let Some(message) = start_message {
+        print_message(term, message, &viewer.settings.theme)?;
     }
 
-    fn prev_pos(& mut self) -> bool {
-        if self.curr_pos > 0 {
-            self.curr_pos -= 1;
-            return true;
-        }
-        false
-    }
-
-    // In here I have to calculate the forward distance from the current position
-    // for all occurrences and get the minimal value.
-    // If it didn't find, it goes to the first one.
-    fn find_next_nearest_pos(& mut self, text_vec: &Vec<char>, global_curr_page_start_pos: usize) -> usize {
-        let mut lowest_distance = text_vec.len() as i32;
-        let mut last_word_index = 0_usize;
-
-        // Search's next in front of current start of current page start position, in reverse, from the end to the current position.
-        for (word_index, text_pos) in self.text_pos_vec.iter().rev().enumerate() {
-           let delta = text_pos.start_pos as i32 - global_curr_page_start_pos as i32;
-           if delta >= 0 && delta < lowest_distance {
-               lowest_distance = delta;
-               last_word_index = self.text_pos_vec.len() - 1 - word_index;
-           }
-        }
-
-        if lowest_distance < text_vec.len() as i32 {
-            // Found word occurrence position in front of the start of current page.
-            self.curr_pos = last_word_index;
-            self.text_pos_vec[last_word_index].start_pos
-        } else {
-            // Returns the global position of first occurrence in the text file.
-            self.curr_pos = 0;
-            self.text_pos_vec[0].start_pos
-        }           
-    }
-
-    fn is_inside_word(& self, global_pos_of_char: usize) -> bool {
-        for text_pos in self.text_pos_vec.iter() {
-            if    global_pos_of_char >= text_pos.start_pos
-               && global_pos_of_char <= text_pos.end_pos {
-                   return true;
-               }
-        }
-        false
-    }
-
-    fn is_inside_current_word(& self, global_pos_of_char: usize) -> bool {
-        if    global_pos_of_char >= self.text_pos_vec[self.curr_pos].start_pos
-            && global_pos_of_char <= self.text_pos_vec[self.curr_pos].end_pos {
-                return true;
+    loop {
+        // The pacing mode and the reading guide go to the next step when its time is over.
+        let time_left = controller.show_step(term, viewer)?;
+        if let (Some(time_left), None) = (time_left, &pending_event) {
+            if !term.poll(time_left)? {
+                controller.apply(term, viewer, SessionCommand::NextStep)?;
+                continue;
             }
-        false
-    }
-
-}
-
-enum SearchMode {
-    NotInMode,
-    EnteringSearchString,
-    BrowsingInSearch,
-}
-
-fn print_events(text_vec: &Vec<char>, pages_vec: & mut PageVec) -> Result<()> {
-    
-    let mut search_mode = SearchMode::NotInMode;
-    let mut search_string = String::new();
-    let mut search_opt: Option<Search> = None;
+        }
 
-    loop {
         // Blocking read
-        let event = read()?;
-       
-        // println!("Event: {:?}\r", event);
-
-        // if event == Event::Key(KeyCode::Char('c').into()) {
-        //     println!("Cursor position: {:?}\r", position());
-        // }
+        let event = match pending_event.take() {
+            Some(event) => event,
+            None => term.read()?,
+        };
 
         if let Event::Resize(_, _) = event {
-            let (original_size, new_size) = flush_resize_events(event);
-            println!("Resize from: {:?}, to: {:?}", original_size, new_size);
-            stdout().execute(terminal::Clear(terminal::ClearType::All)).unwrap();
-
-            // Get the old text char position.
-            let (_page_num, page) = pages_vec.get_curr_page();
-            let cur_start_page_char_pos = page.global_start_char_pos;
-
-            // Do the new pagination.
-            *pages_vec = PageVec::paginate(text_vec, terminal::size());
-            
-            // Find the new page inside the new pagination.
-            let target_page = pages_vec.find_char_pos_in_pages(cur_start_page_char_pos);
-
-            // Set the found page as the current page.
-            pages_vec.set_curr_page_num(target_page);
-
-            let (_page_num, page) = pages_vec.get_curr_page();        
-            let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-            
-            print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                     &search_mode, &search_string);
-        }
-
-        if event == Event::Key(KeyCode::Esc.into()) {
-            execute!(stdout(), terminal::Clear(terminal::ClearType::All), MoveTo(0, 0)).unwrap();
-            break;
+            let ((len_col, len_row), next_event) = flush_resize_events(term, event)?;
+            pending_event = next_event;
+            viewer.apply(Command::Resize(len_col, len_row));
+            render(term, viewer)?;
         }
 
-        match search_mode {
-            SearchMode::NotInMode | SearchMode::BrowsingInSearch  => {
-                    if event == Event::Key(KeyCode::Char('q').into()) && pages_vec.prev_page(){
-                        let (_page_num, page) = pages_vec.get_curr_page();        
-                        let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-                        print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                                 &search_mode, &search_string);
-                    }
-        
-                    if event == Event::Key(KeyCode::Char('a').into()) && pages_vec.next_page(){
-                        let (_page_num, page) = pages_vec.get_curr_page();        
-                        let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-                        print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                                 &search_mode, &search_string);
-                    }
-
-                    // Enter in search mode.
-                    if event == Event::Key(KeyCode::Char('/').into()){
-                        search_string.clear();
-                        search_mode = SearchMode::EnteringSearchString;
-                        let (_page_num, page) = pages_vec.get_curr_page();        
-                        let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-                        print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                                 &search_mode, &search_string);
-                    }
-
-                    if let SearchMode::BrowsingInSearch = search_mode {
-                        if let Some(ref mut search_tmp) = search_opt {
-                            
-                            // We jump to the prev or next word find occurrence.
-                            if   (event == Event::Key(KeyCode::Char('p').into()) && search_tmp.prev_pos())
-                              || (event == Event::Key(KeyCode::Char('n').into()) && search_tmp.next_pos()) {
-                                
-                                // The prev_pos() or the next_pos() as already been made, so we do a get current position.
-                                let (_ocurr_index, TextPos { start_pos, end_pos: _ }) = search_tmp.get_curr_pos();                                                                   
-                                let page_num =  pages_vec.find_char_pos_in_pages(*start_pos);
-                                pages_vec.set_curr_page_num(page_num);
-
-                                let (_page_num, page) = pages_vec.get_curr_page();                                    
-                                let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-                                print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                                        &search_mode, &search_string);
-
-                            }
-                        }
-                    }
-                },
-            SearchMode::EnteringSearchString => {
-                    // Exit search mode.
-                    match event{
-                        Event::Key(key_event) => {
-                                if key_event.code == KeyCode::Enter {
-                                    if !search_string.is_empty() {
-                                        // Do the search in the text.
-                                        
-                                        // TODO: Possible error not found by the compiler, if we make the next line
-                                        // "if let Some(ref search_tmp)"
-                                        // and comment the line a few lines below "search_opt = Some(search_tmp);" 
-                                        if let Some(mut search_tmp) = Search::find(text_vec, &search_string) {
-                                            search_mode = SearchMode::BrowsingInSearch;
-                                            // Go to the page and update the screen.
-                                            let (_page_num, page) = pages_vec.get_curr_page();
-                                            let search_next_pos = search_tmp.find_next_nearest_pos( &text_vec, page.global_start_char_pos);
-                                            let page_num = pages_vec.find_char_pos_in_pages(search_next_pos);
-                                            search_opt = Some(search_tmp);
-                                            pages_vec.set_curr_page_num(page_num);
-                                            let (_page_num, page) = pages_vec.get_curr_page();
-                                            let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-                                            print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                                                     &search_mode, &search_string);
-                                            continue;
-                                        } else {
-                                            search_opt = None;
-                                            search_mode = SearchMode::NotInMode;   
-                                        }
-                                    } else {
-                                        search_opt = None;
-                                        search_mode = SearchMode::NotInMode;
-                                    }
-                                } else if key_event.code == KeyCode::Backspace {
-                                    search_string.pop();                                 
-                                } else if let KeyCode::Char(c) = key_event.code {
-                                    search_string.push(c);
-                                }
-                                let (_page_num, page) = pages_vec.get_curr_page();        
-                                let text_vec_page = text_vec[page.global_start_char_pos..=page.global_stop_char_pos].to_vec();
-                                print_fp(&text_vec_page, &search_opt, page.global_start_char_pos,
-                                         &search_mode, &search_string);
-                            },
-                        // Events processed before this point.
-                        Event::Mouse(_) => (),
-                        Event::Resize(_, _) => (),
-                    };
-                    
-                    
-                },
-            
+        if let Some(command) = controller.command(viewer, &event) {
+            if !controller.apply(term, viewer, command)? {
+                break;
+            }
         }
-
     }
 
     Ok(())
 }
 
+
 // Resize events can occur in batches.
 // With a simple loop they can be flushed.
-// This function will keep the first and last resize event.
-fn flush_resize_events(event: Event) -> ((u16, u16), (u16, u16)) {
+// This function returns the size of the last resize event, and the
+// other event read after them, that is processed next.
+fn flush_resize_events(term: & mut dyn Terminal, event: Event) -> Result<((u16, u16), Option<Event>)> {
+    let mut last_resize = term.size()?;
     if let Event::Resize(x, y) = event {
-        let mut last_resize = (x, y);
-        while let Ok(true) = poll(Duration::from_millis(50)) {
-            if let Ok(Event::Resize(x, y)) = read() {
-                last_resize = (x, y);
-            }
+        last_resize = (x, y);
+    }
+    while term.poll(Duration::from_millis(50))? {
+        match term.read()? {
+            Event::Resize(x, y) => last_resize = (x, y),
+            next_event => return Ok((last_resize, Some(next_event))),
         }
-
-        return ((x, y), last_resize);
     }
-    ((0, 0), (0, 0))
+    Ok((last_resize, None))
 }
 
-struct Page {
-    global_start_char_pos: usize,
-    global_stop_char_pos: usize,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SearchOptions;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use crate::memory_terminal::MemoryTerminal;
 
-struct PageVec {
-    curr_page: usize,
-    pages_vec: Vec<Page>,
-}
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
 
-impl PageVec {
-    fn paginate(text_vec: &Vec<char>, new_size: Result<(u16, u16)>) -> Self {
-        let curr_page: usize = 0;
-        let mut page = Some(Page {global_start_char_pos: 0, global_stop_char_pos: 0});
-        let mut pages_vec: Vec<Page> = Vec::new();
- 
-        let mut cur_row = 0_u16;
-        let mut cur_column = 0_u16;
-
-        let (max_colum, max_row) = new_size.unwrap();
-        let (max_colum, max_row) = (max_colum - 1, max_row - 1);
-
-        // Paginates - Divide the Vec<chars> into the needed pages for the current size.
-        for (i, c) in text_vec.iter().enumerate() {
-            if *c != '\n' {
-                if cur_column < max_colum {
-                    cur_column += 1; 
-                } else {
-                    if max_row == cur_row {
-                        // The text spills to the next page.
-                        // Creates the new page.
-                        if let Some(mut page_tmp) = page {
-                            page_tmp.global_stop_char_pos = i - 1;
-                            pages_vec.push(page_tmp);
-                            page = Some(Page {global_start_char_pos: i, global_stop_char_pos : i});
-                        }
-                        cur_row = 0;
-                    } else {
-                        // Continue on the same page.
-                        cur_row += 1;
-                    }
-                    cur_column = 0;
-                }
-            } else {
-                // New line character.
-                if max_row == cur_row {
-                    // The text spills to the next page.
-                    // Creates the new page.
-                    if let Some(mut page_tmp) = page {
-                        page_tmp.global_stop_char_pos = i - 1;
-                        pages_vec.push(page_tmp);
-                        if i < text_vec.len() - 1 {
-                            // So that /n isn't included in the start of the new page.
-                            page = Some(Page {global_start_char_pos: i + 1, global_stop_char_pos : i + 1});
-                        } else {
-                            page = Some(Page {global_start_char_pos: i, global_stop_char_pos : i});
-                        }
-                    }
-                    cur_row = 0;
-                } else {
-                    // Continue on the same page.
-                    cur_row += 1;
-                }
-                cur_column = 0;
-            }
-        }
+    /// The keys of the chars, ex: "aa" for two next pages.
+    fn keys(chars: &str) -> Vec<Event> {
+        chars.chars().map(|c| key(KeyCode::Char(c))).collect()
+    }
 
-        // Adds the last page to be paginated, can be the first, if it only has one.
-        if let Some(mut page_tmp) = page {
-            page_tmp.global_stop_char_pos = text_vec.len() - 1;
-            pages_vec.push(page_tmp);
+    fn test_settings() -> Settings {
+        Settings {
+            theme: Theme::default(),
+            fixation: FixationStrategy::default(),
+            no_fixation: false,
+            search_options: SearchOptions::default(),
+            keymap: KeyMap::default(),
+            no_init: false,
+            resume: false,
+            wpm: DEFAULT_WPM,
+            auto_advance: false,
         }
+    }
 
-        Self {
-            curr_page,
-            pages_vec,
-        }
+    /// Opens the text in a terminal in memory of the size, reads the events and
+    /// then Esc, and returns the terminal with the last screen.
+    fn run_events(text: &str, size: (u16, u16), events: Vec<Event>) -> MemoryTerminal {
+        run_session(text, size, test_settings(), & mut Bookmarks::unsaved(), events)
     }
 
-    fn get_curr_page(&self) -> (usize, &Page) {
-        (self.curr_page, &self.pages_vec[self.curr_page])
+    /// Like run_events, with the settings and the state of the file.
+    fn run_session(text: &str, (len_col, len_row): (u16, u16), settings: Settings, bookmarks: & mut Bookmarks,
+                   events: Vec<Event>) -> MemoryTerminal {
+        let text_vec: Vec<char> = text.chars().collect();
+        let layout = Layout { max_width: None, center: false, margin: 0, line_numbers: false, word_wrap: false };
+        let mut viewer = Viewer::new(&text_vec, &[], settings, layout, (len_col, len_row));
+        let mut term = MemoryTerminal::new(len_col, len_row);
+        term.push_events(events);
+        term.push_events([key(KeyCode::Esc)]);
+        print_events(& mut term, & mut viewer, &[], bookmarks, &[], None).unwrap();
+        term
     }
 
-    fn set_curr_page_num(& mut self, page_num: usize) -> bool {
-        if page_num < self.pages_vec.len() {
-            self.curr_page = page_num;
-            return true;
-        }
-        false
+    fn numbered_lines(num_lines: usize) -> String {
+        (1..=num_lines).map(|line_num| format!("line {}\n", line_num)).collect()
     }
 
-    fn find_char_pos_in_pages(&self, global_char_pos: usize) -> usize {
-        for (page_num, page) in self.pages_vec.iter().enumerate() {
-            if    global_char_pos >= page.global_start_char_pos
-               && global_char_pos <= page.global_stop_char_pos {
-                   return page_num;
-               }
-        }
-        0_usize
+    #[test]
+    fn first_page_with_fixation_points() {
+        let term = run_events("one two three\nfour five\n", (20, 4), Vec::new());
+        assert_eq!(term.screen(), ["one two three", "four five", "", ""]);
+        // The first char of "one" is in bold, the others aren't.
+        assert!(term.cell(0, 0).style.unwrap().bold);
+        assert!(!term.cell(1, 0).style.unwrap().bold);
+        // "thr" of "three".
+        assert!(term.cell(10, 0).style.unwrap().bold);
+        assert!(!term.cell(11, 0).style.unwrap().bold);
     }
 
-    fn next_page(& mut self) -> bool {
-        if self.curr_page < self.pages_vec.len() - 1 {
-            self.curr_page += 1;
-            return true;
+    #[test]
+    fn number_words_are_bold_like_in_the_export() {
+        let term = run_events("Price 42. x\n", (20, 3), Vec::new());
+        let text_vec: Vec<char> = "Price 42. x".chars().collect();
+        let mask = less_fp::fixation_mask(&text_vec, FixationStrategy::default());
+        for (column, is_bold) in mask.into_iter().enumerate() {
+            assert_eq!(term.cell(column as u16, 0).style.unwrap().bold, is_bold, "column {}", column);
         }
-        false
+        // The '.' after "42".
+        assert!(!term.cell(8, 0).style.unwrap().bold);
     }
 
-    fn prev_page(& mut self) -> bool {
-        if self.curr_page > 0 {
-            self.curr_page -= 1;
-            return true;
-        }
-        false
+    #[test]
+    fn next_and_prev_page() {
+        let text = numbered_lines(10);
+        assert_eq!(run_events(&text, (20, 4), keys("a")).screen(), ["line 5", "line 6", "line 7", "line 8"]);
+        assert_eq!(run_events(&text, (20, 4), keys("aaaa")).screen(), ["line 9", "line 10", "", ""]);
+        assert_eq!(run_events(&text, (20, 4), keys("aqqq")).screen(), ["line 1", "line 2", "line 3", "line 4"]);
     }
 
-}
+    #[test]
+    fn hide_fixation_points() {
+        let term = run_events("one two three\n", (30, 3), keys("f"));
+        assert!(!term.cell(0, 0).style.unwrap().bold);
+        assert_eq!(term.row_text(2), "Fixation points off");
+    }
 
-struct Word {
-    start: usize,
-    _end: usize,
-    middle_start: usize,
-    _middle_end: usize
-}
+    #[test]
+    fn set_mark_and_go_to_it() {
+        let text = numbered_lines(10);
+        let term = run_events(&text, (20, 4), keys("amb"));
+        assert_eq!(term.row_text(3), "Mark 'b' set");
+        let term = run_events(&text, (20, 4), keys("amba'b"));
+        assert_eq!(term.screen(), ["line 5", "line 6", "line 7", "line 8"]);
+        // A key that isn't a letter cancels the mark.
+        let term = run_events(&text, (20, 4), keys("m1a'x"));
+        assert_eq!(term.row_text(0), "line 5");
+        assert_eq!(term.row_text(3), "Mark 'x' isn't set");
+    }
 
-trait Inside {
-    fn is_inside_word_first_half(&self, index: usize) -> bool;
-}
+    #[test]
+    fn reopen_at_the_last_position() {
+        let text = numbered_lines(10);
+        let settings = || Settings { resume: true, ..test_settings() };
+        let mut bookmarks = Bookmarks::unsaved();
+        run_session(&text, (20, 4), settings(), & mut bookmarks, keys("a"));
+        assert_eq!(bookmarks.file_state.last_pos, Some(text.find("line 5").unwrap()));
+        let term = run_session(&text, (20, 4), settings(), & mut bookmarks, Vec::new());
+        assert_eq!(term.screen(), ["line 5", "line 6", "line 7", "line 8"]);
+
+        // A position beyond the end of a shorter text opens at its last page.
+        bookmarks.file_state.last_pos = Some(text.len() + 100);
+        let term = run_session(&numbered_lines(6), (20, 4), settings(), & mut bookmarks, Vec::new());
+        assert_eq!(term.screen(), ["line 5", "line 6", "", ""]);
+
+        // Without resume the position isn't used or saved.
+        bookmarks.file_state.last_pos = Some(text.find("line 5").unwrap());
+        let term = run_session(&text, (20, 4), test_settings(), & mut bookmarks, keys("aa"));
+        assert_eq!(term.row_text(0), "line 9");
+        let term = run_session(&text, (20, 4), settings(), & mut bookmarks, Vec::new());
+        assert_eq!(term.row_text(0), "line 5");
+    }
 
-impl Inside for Vec<Word> {
-    fn is_inside_word_first_half(&self, index: usize) -> bool {
-        for word in self {
-            if    index >= word.start
-               && index <= word.middle_start {
-                // Inside   
-                return true;
-               }
+    #[test]
+    fn line_guide_at_the_last_line_of_the_page() {
+        // The last page has one line, without a new line at the end.
+        let text = numbered_lines(4) + "the end";
+        let term = run_events(&text, (20, 4), keys("agg"));
+        assert_eq!(term.row_text(0), "the end");
+        let theme = Theme::default();
+        for column in 0..7 {
+            assert_eq!(term.cell(column, 0).style.unwrap().colors, theme.guide, "column {}", column);
         }
-        false
-    }    
-}
+        assert!(term.row_text(3).contains("line guide"), "{:?}", term.screen());
+    }
 
-fn print_fp(p_buf: &Vec<char>, search_opt: &Option<Search>, global_start_pos: usize,
-            search_mode: &SearchMode, search_string: &str) {
-    // Find the start and end indices of the words in the String and corrects for a sequence of white spaces or tabs.
-    let mut words_index: Vec<Word> = Vec::new();
-    let mut flag_inside_word = false;
-    let mut start = 0 ;
-    for (i, c) in p_buf.iter().enumerate() {
-        if c.is_alphanumeric() {
-            if !flag_inside_word {
-                flag_inside_word = true;
-                start = i;
-            }
-        } else if flag_inside_word {
-            flag_inside_word = false;
-            let end = i;
-            let middle_start;
-            let middle_end;
-            // If word starts with a number, all the word will be bold :-)
-            if p_buf[start].is_numeric() {
-                (middle_start, middle_end) = (end, end);
-            } else {
-                (middle_start, middle_end) = calc_middle_start_end_point(start, end);
-            }
-            words_index.push(Word {start, _end: end, middle_start, _middle_end: middle_end});
-        }
+    #[test]
+    fn pacing_speed_in_the_status() {
+        let term = run_events(&numbered_lines(10), (20, 4), keys(" ++-"));
+        assert!(term.row_text(3).contains("▶ 275 wpm"), "{:?}", term.screen());
     }
 
-    execute!(stdout(), terminal::Clear(terminal::ClearType::All)).unwrap();
-    let _ = execute!(stdout(), MoveTo(0, 0));
+    #[test]
+    fn search_goes_to_the_match() {
+        let text = numbered_lines(10) + "the end\n" + &numbered_lines(10);
+        let mut events = keys("/end");
+        events.push(key(KeyCode::Enter));
+        let term = run_events(&text, (20, 4), events);
+        assert_eq!(term.screen(), ["line 9", "line 10", "the end", "line 1"]);
+        let theme = Theme::default();
+        assert_eq!(term.cell(4, 2).style.unwrap().colors, theme.current_match);
+        assert_eq!(term.cell(3, 2).style.unwrap().colors, theme.text);
+
+        // The next matches, "line 10" and then "line 1" on the same page.
+        let mut events = keys("/line 1");
+        events.push(key(KeyCode::Enter));
+        events.extend(keys("nn"));
+        let term = run_events(&text, (20, 4), events);
+        assert_eq!(term.screen(), ["line 9", "line 10", "the end", "line 1"]);
+        let theme = Theme::default();
+        assert_eq!(term.cell(0, 3).style.unwrap().colors, theme.current_match);
+    }
 
-    // Prints in bold and normal, the text on the terminal.
-    for (i, c) in p_buf.iter().enumerate() {
-        let mut flag_search_inside_word = false;
-        let mut flag_search_inside_current_word = false;
-        if let Some(ref search_tmp ) = search_opt {
-            flag_search_inside_word = search_tmp.is_inside_word(global_start_pos + i); 
-            flag_search_inside_current_word = search_tmp.is_inside_current_word(global_start_pos + i); 
-        }
+    #[test]
+    fn search_prompt_and_not_found() {
+        let text = numbered_lines(3);
+        let term = run_events(&text, (20, 4), keys("/lin"));
+        assert_eq!(term.row_text(3), "/ lin");
+
+        let mut events = keys("/xyz");
+        events.push(key(KeyCode::Backspace));
+        events.push(key(KeyCode::Enter));
+        events.extend(keys("n"));
+        let term = run_events(&text, (20, 4), events);
+        assert_eq!(term.screen(), ["line 1", "line 2", "line 3", ""]);
+    }
 
-        if *c == '\n' {
-            let (_col, row) = position().unwrap();
-            execute!(stdout(), MoveTo(0, row + 1), SetColors(Colors::new(Green, COLOR_REAL_BLACK)) ).unwrap();
-        } else if words_index.is_inside_word_first_half(i) {
-            if flag_search_inside_current_word {
-                execute!(stdout(), SetColors(Colors::new(DarkGrey, White)), Print(&(*c.to_string()).bold()) ).unwrap();
-            } else if flag_search_inside_word {
-                execute!(stdout(), SetColors(Colors::new(Blue, White)), Print(&(*c.to_string())) ).unwrap();
-            } else {
-                execute!(stdout(), SetColors(Colors::new(Green, COLOR_REAL_BLACK)), Print(&(*c.to_string()).bold()) ).unwrap();
-            }
-        } else if *c != '\n' {
-
-            if flag_search_inside_current_word {
-                execute!(stdout(), SetColors(Colors::new(DarkGrey, White)), Print(&(*c.to_string()).bold()) ).unwrap();
-            } else if flag_search_inside_word {
-                execute!(stdout(), SetColors(Colors::new(Blue, White)), Print( &(*c.to_string())) ).unwrap();
-            } else {
-                execute!(stdout(), SetColors(Colors::new(Green, COLOR_REAL_BLACK)), Print( &(*c.to_string())) ).unwrap();
-            }
-        } else {
-            let (_col, row) = position().unwrap();
-            execute!(stdout(), MoveTo(0, row + 1), SetColors(Colors::new(Green, COLOR_REAL_BLACK)) ).unwrap();
-        }
+    #[test]
+    fn remapped_letters_are_typed_in_the_search() {
+        let text = numbered_lines(10) + "a box\n";
+        let keymap = KeyMap::with_remap(&toml::from_str("quit = [\"x\", \"Esc\"]").unwrap()).unwrap();
+        let settings = Settings { keymap, ..test_settings() };
+        let mut events = keys("/a box");
+        events.push(key(KeyCode::Enter));
+        let term = run_session(&text, (20, 4), settings, & mut Bookmarks::unsaved(), events);
+        assert_eq!(term.screen(), ["line 9", "line 10", "a box", ""]);
+
+        // The remapped quit key quits after the search.
+        let keymap = KeyMap::with_remap(&toml::from_str("quit = \"x\"").unwrap()).unwrap();
+        let settings = Settings { keymap, ..test_settings() };
+        let mut events = keys("/box");
+        events.push(key(KeyCode::Enter));
+        events.extend(keys("x"));
+        let term = run_session(&text, (20, 4), settings, & mut Bookmarks::unsaved(), events);
+        assert_eq!(term.screen(), ["line 9", "line 10", "a box", ""]);
     }
 
-    match search_mode {
-        SearchMode::NotInMode => (),
-        SearchMode::EnteringSearchString => {
-            let (_len_col, len_row) = terminal::size().unwrap();
-            let string_out = "/ ".to_string() + search_string;
-            execute!(stdout(), MoveTo(0, len_row - 1), SetColors(Colors::new(White, DarkBlue)), Print( &(string_out)) ).unwrap();
-            execute!(stdout(), SetColors(Colors::new(Green, COLOR_REAL_BLACK)) ).unwrap();
-            },
-        SearchMode::BrowsingInSearch => (),
+    #[test]
+    fn resize_repaginates_at_the_same_text() {
+        let text = numbered_lines(10);
+        let term = run_events(&text, (20, 4), vec![Event::Resize(20, 12)]);
+        assert_eq!(term.row_text(0), "line 1");
+        assert_eq!(term.row_text(9), "line 10");
+
+        // The page is the one with the start of the page before, and the key after the resize isn't lost.
+        let mut events = keys("a");
+        events.push(Event::Resize(4, 3));
+        let term = run_events(&text, (20, 4), events.clone());
+        assert_eq!(term.screen(), ["line", " 4", "line"]);
+        events.extend(keys("a"));
+        let term = run_events(&text, (20, 4), events);
+        assert_eq!(term.screen(), [" 5", "line", " 6"]);
     }
 
-}
+    #[test]
+    fn resize_to_one_cell_and_back() {
+        let text = numbered_lines(5);
+        let mut events = vec![Event::Resize(1, 1)];
+        events.extend(keys("aql[]c"));
+        events.push(Event::Resize(20, 6));
+        let term = run_events(&text, (20, 6), events);
+        // With the line numbers.
+        assert_eq!(term.row_text(0), "1 line 1");
+    }
 
-/// Returns (middle_start, middle_end)
-fn calc_middle_start_end_point(word_start: usize, word_end: usize) -> (usize, usize) {
-    let len = word_end - word_start;    
-    // É Impar?
-    let is_odd = len % 2 != 0; 
-    let middle_f32 = len as f32 / 2.0;
-    let _exact_value = middle_f32 % 1.0;
-
-    let middle_start;
-    let middle_end;
-    if len >= 5 && is_odd {
-        middle_start = word_start + middle_f32 as usize;
-        middle_end = word_start + middle_f32 as usize + 1;
-    } else if len == 1 {
-        middle_start = word_start;
-        middle_end = word_start;
-    } else {
-        middle_start = word_start + middle_f32 as usize - 1;
-        middle_end = word_start + middle_f32 as usize;
+    #[test]
+    fn empty_file() {
+        let mut events = keys("aqlfn[]c/x");
+        events.push(key(KeyCode::Enter));
+        events.push(Event::Resize(10, 2));
+        events.extend(keys("r'a"));
+        let term = run_events("", (20, 4), events);
+        assert_eq!(term.row_text(0), "");
+        assert_eq!(term.row_text(1), "Mark 'a' i");
     }
 
-    (middle_start, middle_end)
+    #[test]
+    fn file_of_only_new_lines() {
+        let term = run_events("\n\n\n\n\n\n", (10, 3), keys("l"));
+        assert_eq!(term.screen(), ["1", "2", "3"]);
+        let term = run_events("\n\n\n\n\n\n", (10, 3), keys("la"));
+        assert_eq!(term.screen(), ["4", "5", "6"]);
+        let term = run_events("\n\n\n\n\n\n", (10, 3), keys("aaa"));
+        assert_eq!(term.screen(), ["", "", ""]);
+    }
+
+    #[test]
+    fn single_long_line() {
+        let text = "abcdefghij".repeat(5);
+        let term = run_events(&text, (10, 2), Vec::new());
+        assert_eq!(term.screen(), ["abcdefghij", "abcdefghij"]);
+        let term = run_events(&text, (10, 2), keys("aaa"));
+        assert_eq!(term.screen(), ["abcdefghij", ""]);
+        // The rows that continue the line are marked in the gutter.
+        let term = run_events(&text, (10, 2), keys("l"));
+        assert_eq!(term.screen(), ["1 abcdefgh", "↪ ijabcdef"]);
+    }
 }
@@ -0,0 +1,350 @@
+// The Markdown mode, the Markdown text is rendered to the text that is shown,
+// without the markup, and to the style spans of the headings, emphasis, code
+// and links. The pagination and the search work on the rendered text, and the
+// fixation points aren't shown in the code.
+
+use crossterm::style::Color;
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag};
+
+use crate::string_utils::StringUtils;
+use crate::style::{push_char_style, StyleSpan, TextStyle};
+
+const CODE_COLOR: Color = Color::DarkCyan;
+const RULE_WIDTH: usize = 40;
+
+/// The file extensions of the Markdown files, that are opened in the Markdown mode.
+pub const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+struct Renderer {
+    text_vec: Vec<char>,
+    spans_vec: Vec<StyleSpan>,
+    // The styles of the nested inline elements, the last one is the current style.
+    style_stack: Vec<TextStyle>,
+    // The next number of the ordered lists, or None for the bullet lists.
+    list_stack: Vec<Option<u64>>,
+    quote_depth: usize,
+    in_code_block: bool,
+    // The destinations of the links, shown after the link text.
+    link_stack: Vec<String>,
+    num_table_cell: usize,
+    // After the marker of a list item, where its first block starts.
+    at_item_start: bool,
+}
+
+/// Renders the Markdown text and returns the rendered text and its style spans.
+pub fn render_markdown(text: &str) -> (Vec<char>, Vec<StyleSpan>) {
+    let mut renderer = Renderer {
+        text_vec: Vec::with_capacity(text.len()),
+        spans_vec: Vec::new(),
+        style_stack: vec![TextStyle::default()],
+        list_stack: Vec::new(),
+        quote_depth: 0,
+        in_code_block: false,
+        link_stack: Vec::new(),
+        num_table_cell: 0,
+        at_item_start: false,
+    };
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH
+                  | Options::ENABLE_TASKLISTS | Options::ENABLE_FOOTNOTES;
+    for event in Parser::new_ext(text, options) {
+        renderer.event(event);
+    }
+
+    // Removes the blank lines at the end.
+    while renderer.text_vec.last() == Some(&'\n') {
+        renderer.text_vec.pop();
+    }
+    renderer.text_vec.push('\n');
+    let len = renderer.text_vec.len();
+    renderer.spans_vec.retain(|span| span.start_pos < len);
+    if let Some(last_span) = renderer.spans_vec.last_mut() {
+        last_span.end_pos = last_span.end_pos.min(len - 1);
+    }
+    (renderer.text_vec, renderer.spans_vec)
+}
+
+impl Renderer {
+    fn event(& mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.push_str(&text),
+            Event::Code(code) => {
+                    self.push_style(|style| { style.fg = Some(CODE_COLOR); style.no_fixation = true; });
+                    self.push_str(&code);
+                    self.pop_style();
+                },
+            Event::Html(html) => {
+                    // The tags are removed, except the line breaks.
+                    let tag = html.trim().to_lowercase();
+                    if tag == "<br>" || tag == "<br/>" || tag == "<br />" {
+                        self.push_char('\n');
+                    }
+                },
+            Event::FootnoteReference(label) => self.push_str(&format!("[{}]", label)),
+            Event::SoftBreak => self.push_char(' '),
+            Event::HardBreak => self.push_char('\n'),
+            Event::Rule => {
+                    self.end_block();
+                    self.push_str(&"─".repeat(RULE_WIDTH));
+                    self.end_block();
+                },
+            Event::TaskListMarker(checked) => self.push_str(if checked { "[x] " } else { "[ ] " }),
+        }
+    }
+
+    fn start_tag(& mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.end_block(),
+            Tag::Heading(level, _id, _classes) => {
+                    self.end_block();
+                    self.push_style(|style| {
+                        style.bold = true;
+                        style.underline = level <= HeadingLevel::H2;
+                    });
+                },
+            Tag::BlockQuote => {
+                    self.end_block();
+                    self.quote_depth += 1;
+                },
+            Tag::CodeBlock(_kind) => {
+                    self.end_block();
+                    self.in_code_block = true;
+                    self.push_style(|style| { style.fg = Some(CODE_COLOR); style.no_fixation = true; });
+                },
+            Tag::List(first_number) => {
+                    if self.list_stack.is_empty() {
+                        self.end_block();
+                    }
+                    self.list_stack.push(first_number);
+                },
+            Tag::Item => {
+                    self.end_line();
+                    let indent = "  ".repeat(self.list_stack.len().saturating_sub(1));
+                    let marker = match self.list_stack.last_mut() {
+                        Some(Some(number)) => {
+                                *number += 1;
+                                format!("{}. ", *number - 1)
+                            },
+                        _ => "• ".to_string(),
+                    };
+                    self.push_line_prefix();
+                    self.text_vec.extend(format!("{}{}", indent, marker).chars());
+                    self.at_item_start = true;
+                },
+            Tag::FootnoteDefinition(label) => {
+                    self.end_block();
+                    self.push_str(&format!("[{}]: ", label));
+                },
+            Tag::Table(_alignments) => self.end_block(),
+            Tag::TableHead => self.push_style(|style| style.bold = true),
+            Tag::TableRow => self.num_table_cell = 0,
+            Tag::TableCell => {
+                    if self.num_table_cell > 0 {
+                        self.push_str(" │ ");
+                    }
+                    self.num_table_cell += 1;
+                },
+            Tag::Emphasis => self.push_style(|style| style.italic = true),
+            Tag::Strong => self.push_style(|style| style.bold = true),
+            Tag::Strikethrough => self.push_style(|_style| ()),
+            Tag::Link(_link_type, url, _title) => {
+                    self.link_stack.push(url.to_string());
+                    self.push_style(|style| style.underline = true);
+                },
+            Tag::Image(_link_type, _url, _title) => {
+                    self.push_style(|style| style.italic = true);
+                    self.push_str("[image: ");
+                },
+        }
+    }
+
+    fn end_tag(& mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::Table(_) | Tag::FootnoteDefinition(_) => self.end_block(),
+            Tag::Heading(..) => {
+                    self.pop_style();
+                    self.end_block();
+                },
+            Tag::BlockQuote => {
+                    self.end_block();
+                    self.quote_depth -= 1;
+                },
+            Tag::CodeBlock(_kind) => {
+                    self.pop_style();
+                    self.in_code_block = false;
+                    self.end_block();
+                },
+            Tag::List(_first_number) => {
+                    self.list_stack.pop();
+                    if self.list_stack.is_empty() {
+                        self.end_block();
+                    }
+                },
+            Tag::Item => self.end_line(),
+            Tag::TableHead => {
+                    self.pop_style();
+                    self.end_line();
+                },
+            Tag::TableRow => self.end_line(),
+            Tag::TableCell => (),
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => self.pop_style(),
+            Tag::Link(..) => {
+                    self.pop_style();
+                    let url = self.link_stack.pop().unwrap_or_default();
+                    // The URL isn't repeated when it is the link text, ex: <https://example.com>.
+                    if !url.is_empty() && !self.text_vec.ends_with(&url.chars().collect::<Vec<char>>()) {
+                        self.push_style(|style| style.no_fixation = true);
+                        self.push_str(&format!(" <{}>", url));
+                        self.pop_style();
+                    }
+                },
+            Tag::Image(..) => {
+                    self.push_str("]");
+                    self.pop_style();
+                },
+        }
+    }
+
+    /// Pushes a new style, that is the current style changed by the function.
+    fn push_style(& mut self, change: impl FnOnce(& mut TextStyle)) {
+        let mut style = self.current_style();
+        change(& mut style);
+        self.style_stack.push(style);
+    }
+
+    fn pop_style(& mut self) {
+        if self.style_stack.len() > 1 {
+            self.style_stack.pop();
+        }
+    }
+
+    fn current_style(&self) -> TextStyle {
+        *self.style_stack.last().unwrap()
+    }
+
+    fn at_line_start(&self) -> bool {
+        matches!(self.text_vec.last(), None | Some('\n'))
+    }
+
+    /// The quote bars, the indentation of the list items and of the code blocks.
+    fn push_line_prefix(& mut self) {
+        let mut prefix = "│ ".repeat(self.quote_depth);
+        if self.in_code_block {
+            prefix.push_str(&"  ".repeat(self.list_stack.len()));
+            prefix.push_str("    ");
+        }
+        for c in prefix.chars() {
+            self.text_vec.push(c);
+        }
+    }
+
+    fn push_str(& mut self, text: &str) {
+        for c in text.get_vec_chars() {
+            self.push_char(c);
+        }
+    }
+
+    fn push_char(& mut self, c: char) {
+        if c != '\n' && self.at_line_start() {
+            self.push_line_prefix();
+            if !self.in_code_block && !self.list_stack.is_empty() {
+                // The continuation lines of a list item.
+                let indent = "  ".repeat(self.list_stack.len());
+                self.text_vec.extend(indent.chars());
+            }
+        }
+        self.at_item_start = false;
+        let style = self.current_style();
+        push_char_style(& mut self.spans_vec, self.text_vec.len(), style);
+        self.text_vec.push(c);
+    }
+
+    /// Ends the current line, if it isn't empty.
+    fn end_line(& mut self) {
+        if !self.at_line_start() && !self.at_item_start {
+            self.text_vec.push('\n');
+        }
+    }
+
+    /// Ends the current block with a blank line, before the next block.
+    fn end_block(& mut self) {
+        if self.at_item_start {
+            return;
+        }
+        self.end_line();
+        if !self.text_vec.is_empty() && !self.text_vec.ends_with(&['\n', '\n']) {
+            self.text_vec.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(markdown: &str) -> (String, Vec<StyleSpan>) {
+        let (text_vec, spans_vec) = render_markdown(markdown);
+        (text_vec.iter().collect(), spans_vec)
+    }
+
+    /// The texts of the spans with a style that matches.
+    fn styled(markdown: &str, matches: fn(&TextStyle) -> bool) -> Vec<String> {
+        let (text_vec, spans_vec) = render_markdown(markdown);
+        spans_vec.iter()
+                 .filter(|span| matches(&span.style))
+                 .map(|span| text_vec[span.start_pos..=span.end_pos].iter().collect())
+                 .collect()
+    }
+
+    #[test]
+    fn headings_and_emphasis() {
+        let markdown = "# Title\n\nSome *it* and **bold**.\n";
+        assert_eq!(render(markdown).0, "Title\n\nSome it and bold.\n");
+        assert_eq!(styled(markdown, |style| style.bold), ["Title", "bold"]);
+        assert_eq!(styled(markdown, |style| style.underline), ["Title"]);
+        assert_eq!(styled(markdown, |style| style.italic), ["it"]);
+    }
+
+    #[test]
+    fn nested_lists() {
+        let markdown = "1. one\n   - sub a\n   - sub b\n2. two\n\nAfter\n";
+        assert_eq!(render(markdown).0, "1. one\n  • sub a\n  • sub b\n2. two\n\nAfter\n");
+    }
+
+    #[test]
+    fn loose_list_paragraphs() {
+        let markdown = "- first\n\n  more of first\n\n- second\n";
+        assert_eq!(render(markdown).0, "• first\n\n  more of first\n\n• second\n");
+    }
+
+    #[test]
+    fn blockquote() {
+        let markdown = "> quoted\n> text\n\n> > nested\n";
+        assert_eq!(render(markdown).0, "│ quoted text\n\n│ │ nested\n");
+    }
+
+    #[test]
+    fn code_block_without_fixation_points() {
+        let markdown = "Before\n\n```\nlet x = 1;\nx += 1;\n```\n\nAfter\n";
+        assert_eq!(render(markdown).0, "Before\n\n    let x = 1;\n    x += 1;\n\nAfter\n");
+        // The indentation isn't styled, the code is.
+        assert_eq!(styled(markdown, |style| style.no_fixation && style.fg == Some(CODE_COLOR)),
+                   ["let x = 1;\n", "x += 1;\n"]);
+    }
+
+    #[test]
+    fn inline_code() {
+        let markdown = "Call `main()` now\n";
+        assert_eq!(render(markdown).0, "Call main() now\n");
+        assert_eq!(styled(markdown, |style| style.no_fixation), ["main()"]);
+    }
+
+    #[test]
+    fn links_show_their_url() {
+        let markdown = "See [the docs](https://docs.rs) and <https://example.com>.\n";
+        assert_eq!(render(markdown).0, "See the docs <https://docs.rs> and https://example.com.\n");
+        assert_eq!(styled(markdown, |style| style.underline), ["the docs", "https://example.com"]);
+        assert_eq!(styled(markdown, |style| style.no_fixation), [" <https://docs.rs>"]);
+    }
+}
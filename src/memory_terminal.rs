@@ -0,0 +1,125 @@
+// The terminal in memory of the tests, the screen is a grid of cells and the
+// events are read from a queue, so the pages can be driven by a sequence of
+// keys and their screen checked without a terminal.
+
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+use crossterm::{event::Event, style::Colors, Result};
+
+use crate::backend::{CellStyle, Terminal};
+
+/// A char on the screen with its style.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cell {
+    pub c: char,
+    pub style: Option<CellStyle>,
+}
+
+const EMPTY_CELL: Cell = Cell { c: ' ', style: None };
+
+/// A terminal in memory, the screen is a grid of cells and the events are
+/// read from a queue. A resize event changes the size when it is read.
+pub struct MemoryTerminal {
+    len_col: u16,
+    len_row: u16,
+    cursor: (u16, u16),
+    cells: Vec<Cell>,
+    events: VecDeque<Event>,
+}
+
+impl MemoryTerminal {
+    pub fn new(len_col: u16, len_row: u16) -> Self {
+        MemoryTerminal {
+            len_col,
+            len_row,
+            cursor: (0, 0),
+            cells: vec![EMPTY_CELL; len_col as usize * len_row as usize],
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Adds the events to the queue that is read.
+    pub fn push_events(& mut self, events: impl IntoIterator<Item = Event>) {
+        self.events.extend(events);
+    }
+
+    pub fn cell(&self, column: u16, row: u16) -> Cell {
+        self.cells[row as usize * self.len_col as usize + column as usize]
+    }
+
+    /// The chars of the row, without the spaces at the end.
+    pub fn row_text(&self, row: u16) -> String {
+        let text: String = (0..self.len_col).map(|column| self.cell(column, row).c).collect();
+        text.trim_end().to_string()
+    }
+
+    /// The rows of the screen.
+    pub fn screen(&self) -> Vec<String> {
+        (0..self.len_row).map(|row| self.row_text(row)).collect()
+    }
+
+    fn fill(& mut self, from: usize, to: usize, colors: Colors) {
+        for cell in self.cells[from..to].iter_mut() {
+            *cell = Cell { c: ' ', style: Some(CellStyle::plain(colors)) };
+        }
+    }
+}
+
+impl Terminal for MemoryTerminal {
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok((self.len_col, self.len_row))
+    }
+
+    fn move_to(& mut self, column: u16, row: u16) -> Result<()> {
+        self.cursor = (column, row);
+        Ok(())
+    }
+
+    fn print(& mut self, text: &str, style: CellStyle) -> Result<()> {
+        for c in text.chars() {
+            let (column, row) = self.cursor;
+            match c {
+                '\r' => self.cursor = (0, row),
+                '\n' => self.cursor = (column, row.saturating_add(1)),
+                _ => {
+                        // The chars outside of the screen are lost.
+                        if column < self.len_col && row < self.len_row {
+                            self.cells[row as usize * self.len_col as usize + column as usize] = Cell { c, style: Some(style) };
+                        }
+                        self.cursor = (column.saturating_add(1), row);
+                    },
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(& mut self, colors: Colors) -> Result<()> {
+        self.fill(0, self.cells.len(), colors);
+        Ok(())
+    }
+
+    fn clear_until_new_line(& mut self, colors: Colors) -> Result<()> {
+        let (column, row) = self.cursor;
+        if row < self.len_row {
+            let row_start = row as usize * self.len_col as usize;
+            self.fill(row_start + column.min(self.len_col) as usize, row_start + self.len_col as usize, colors);
+        }
+        Ok(())
+    }
+
+    fn poll(& mut self, _timeout: Duration) -> Result<bool> {
+        Ok(!self.events.is_empty())
+    }
+
+    fn read(& mut self) -> Result<Event> {
+        let event = self.events.pop_front()
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "No more events"))?;
+        if let Event::Resize(len_col, len_row) = event {
+            // The contents of a resized terminal are lost.
+            *self = MemoryTerminal { events: std::mem::take(& mut self.events), ..MemoryTerminal::new(len_col, len_row) };
+        }
+        Ok(event)
+    }
+}
@@ -0,0 +1,167 @@
+// The pacing mode, the pages advance automatically at a target speed in words
+// per minute, the time of each page is the time to read its words.
+
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_WPM: u16 = 250;
+const WPM_STEP: u16 = 25;
+const MIN_WPM: u16 = 50;
+const MAX_WPM: u16 = 2000;
+/// The pages without words, ex: blank pages, are shown for this time.
+pub const MIN_PAGE_DURATION: Duration = Duration::from_secs(1);
+
+pub struct Pacing {
+    pub wpm: u16,
+    pub running: bool,
+    // The pacing was started, its status is shown also while paused.
+    started: bool,
+    // The page that is being timed and when it was shown.
+    page_num: usize,
+    page_start: Instant,
+}
+
+impl Pacing {
+    pub fn new(wpm: u16, running: bool) -> Self {
+        Pacing {
+            wpm: wpm.clamp(MIN_WPM, MAX_WPM),
+            running,
+            started: running,
+            page_num: usize::MAX,
+            page_start: Instant::now(),
+        }
+    }
+
+    /// Starts or pauses, the time of the page starts again.
+    pub fn toggle(& mut self) {
+        self.running = !self.running;
+        self.started = true;
+        self.page_start = Instant::now();
+    }
+
+    pub fn faster(& mut self) {
+        self.wpm = (self.wpm + WPM_STEP).min(MAX_WPM);
+    }
+
+    pub fn slower(& mut self) {
+        self.wpm = self.wpm.saturating_sub(WPM_STEP).max(MIN_WPM);
+    }
+
+    /// The time to read the words at the current speed.
+    fn page_duration(&self, num_words: usize) -> Duration {
+        let duration = Duration::from_secs_f64(num_words as f64 * 60.0 / self.wpm as f64);
+        duration.max(MIN_PAGE_DURATION)
+    }
+
+    /// The time left before the next page, or None when paused. The time
+    /// starts when the page is shown, also when it is changed by a key.
+    pub fn time_left(& mut self, page_num: usize, num_words: usize) -> Option<Duration> {
+        if !self.running {
+            return None;
+        }
+        if page_num != self.page_num {
+            self.page_num = page_num;
+            self.page_start = Instant::now();
+        }
+        let deadline = self.page_start + self.page_duration(num_words);
+        Some(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// The status shown on the screen, ex: "▶ 250 wpm" or "‖ 250 wpm" when paused, or None before the pacing is started.
+    pub fn status(&self) -> Option<String> {
+        if !self.started {
+            return None;
+        }
+        let symbol = if self.running { '▶' } else { '‖' };
+        Some(format!(" {} {} wpm ", symbol, self.wpm))
+    }
+}
+
+/// The number of words of the text, the runs of alphanumeric chars.
+pub fn count_words(text_vec: &[char]) -> usize {
+    let mut num_words = 0_usize;
+    let mut inside_word = false;
+    for c in text_vec {
+        if c.is_alphanumeric() {
+            if !inside_word {
+                num_words += 1;
+                inside_word = true;
+            }
+        } else {
+            inside_word = false;
+        }
+    }
+    num_words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The time passed between the calls in a test.
+    const MARGIN: Duration = Duration::from_millis(200);
+
+    fn assert_about(time_left: Option<Duration>, expected: Duration) {
+        let time_left = time_left.unwrap();
+        assert!(time_left <= expected && time_left + MARGIN >= expected, "{:?} isn't about {:?}", time_left, expected);
+    }
+
+    #[test]
+    fn time_of_the_words_of_the_page() {
+        let mut pacing = Pacing::new(300, true);
+        // 100 words at 300 wpm are 20 seconds.
+        assert_about(pacing.time_left(0, 100), Duration::from_secs(20));
+        pacing.faster();
+        assert_about(pacing.time_left(1, 100), Duration::from_secs_f64(100.0 * 60.0 / 325.0));
+    }
+
+    #[test]
+    fn time_of_the_pages_without_words() {
+        // The blank pages at the start or at the end of the text.
+        let mut pacing = Pacing::new(DEFAULT_WPM, true);
+        assert_about(pacing.time_left(0, 0), MIN_PAGE_DURATION);
+        assert_about(pacing.time_left(7, 1), MIN_PAGE_DURATION);
+    }
+
+    #[test]
+    fn time_starts_when_the_page_changes() {
+        let mut pacing = Pacing::new(600, true);
+        assert_about(pacing.time_left(3, 10), Duration::from_secs(1));
+        std::thread::sleep(Duration::from_millis(300));
+        // The same page keeps its deadline.
+        let time_left = pacing.time_left(3, 10).unwrap();
+        assert!(time_left <= Duration::from_millis(700));
+        assert_about(pacing.time_left(4, 10), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn no_time_left_when_paused() {
+        let mut pacing = Pacing::new(DEFAULT_WPM, false);
+        assert_eq!(pacing.time_left(0, 100), None);
+        assert_eq!(pacing.status(), None);
+        pacing.toggle();
+        assert!(pacing.time_left(0, 100).is_some());
+        pacing.toggle();
+        assert_eq!(pacing.time_left(0, 100), None);
+        assert_eq!(pacing.status().as_deref(), Some(" ‖ 250 wpm "));
+    }
+
+    #[test]
+    fn speed_is_never_zero() {
+        let mut pacing = Pacing::new(0, true);
+        assert_eq!(pacing.wpm, MIN_WPM);
+        assert_about(pacing.time_left(0, 10), Duration::from_secs(12));
+        pacing.slower();
+        assert_eq!(pacing.wpm, MIN_WPM);
+        let mut pacing = Pacing::new(u16::MAX, true);
+        assert_eq!(pacing.wpm, MAX_WPM);
+        pacing.faster();
+        assert_eq!(pacing.wpm, MAX_WPM);
+    }
+
+    #[test]
+    fn count_the_words() {
+        let text_vec: Vec<char> = "Don't  stop, 42 times!\n".chars().collect();
+        assert_eq!(count_words(&text_vec), 5);
+        assert_eq!(count_words(&[]), 0);
+    }
+}
@@ -0,0 +1,166 @@
+// The reflow of the hard-wrapped paragraphs of the plain-text books, ex: the
+// Project Gutenberg files wrapped at about 70 columns. The lines of a
+// paragraph are joined and then word wrapped to the width of the reading
+// column. The lines that were broken on purpose, the verse, the list items
+// and the indented lines, are kept.
+
+/// The texts wrapped to fewer columns than this aren't reflowed.
+const MIN_WRAP_WIDTH: usize = 30;
+
+/// Joins the lines inside the paragraphs, the new lines are replaced by spaces,
+/// so the positions of the chars and of their styles don't change.
+pub fn reflow_paragraphs(text_vec: & mut [char]) {
+    let wrap_width = match estimate_wrap_width(text_vec) {
+        Some(wrap_width) => wrap_width,
+        None => return,
+    };
+    // A line was broken by the wrapping when the next word didn't fit, with some slack.
+    let min_broken_len = wrap_width - wrap_width / 6;
+
+    let mut line_start = 0_usize;
+    while let Some(line_len) = text_vec[line_start..].iter().position(|c| *c == '\n') {
+        let new_line_pos = line_start + line_len;
+        let next_start = new_line_pos + 1;
+        if next_start >= text_vec.len() {
+            break;
+        }
+        let line = &text_vec[line_start..new_line_pos];
+        let next_line_len = text_vec[next_start..].iter().position(|c| *c == '\n').unwrap_or(text_vec.len() - next_start);
+        let next_line = &text_vec[next_start..next_start + next_line_len];
+        let next_word_len = next_line.iter().take_while(|c| !c.is_whitespace()).count();
+
+        let is_wrapped =    !line.iter().all(|c| c.is_whitespace())
+                         && !next_line.is_empty()
+                         && !next_line[0].is_whitespace()
+                         && !is_list_item(next_line)
+                         && line.len() + 1 + next_word_len > min_broken_len;
+        if is_wrapped {
+            text_vec[new_line_pos] = ' ';
+        }
+        line_start = next_start;
+    }
+}
+
+/// The width where the text was wrapped, the length of the long lines, or None
+/// if the text has few long lines, ex: the text isn't hard-wrapped.
+fn estimate_wrap_width(text_vec: &[char]) -> Option<usize> {
+    let mut line_lens: Vec<usize> = text_vec.split(|c| *c == '\n')
+                                            .map(|line| line.len())
+                                            .filter(|line_len| *line_len > 0)
+                                            .collect();
+    if line_lens.is_empty() {
+        return None;
+    }
+    line_lens.sort_unstable();
+    // Some lines are longer than the wrap width, ex: the ones that couldn't be broken.
+    let wrap_width = line_lens[line_lens.len() * 95 / 100];
+    if wrap_width < MIN_WRAP_WIDTH {
+        return None;
+    }
+    Some(wrap_width)
+}
+
+/// The line starts with a bullet or a number, ex: "- ", "* ", "• ", "1. " or "a) ".
+fn is_list_item(line: &[char]) -> bool {
+    match line {
+        ['-' | '*' | '+' | '•', ' ', ..] => true,
+        _ => {
+            let marker_len = line.iter().take_while(|c| c.is_ascii_digit()).count().max(
+                                 if line.first().is_some_and(|c| c.is_ascii_lowercase()) { 1 } else { 0 });
+            (1..=3).contains(&marker_len)
+                && matches!(line.get(marker_len), Some('.' | ')'))
+                && line.get(marker_len + 1) == Some(&' ')
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The start of a Project Gutenberg book, wrapped at about 70 columns.
+    const ALICE: &str = "\
+CHAPTER I.
+Down the Rabbit-Hole
+
+
+Alice was beginning to get very tired of sitting by her sister on the
+bank, and of having nothing to do: once or twice she had peeped into the
+book her sister was reading, but it had no pictures or conversations in
+it, \"and what is the use of a book,\" thought Alice \"without pictures or
+conversations?\"
+
+  \"How doth the little crocodile
+    Improve his shining tail,
+  And pour the waters of the Nile
+    On every golden scale!\"
+
+Then she sang the song that the Hatter had taught her at the tea party:
+
+Twinkle, twinkle, little bat!
+How I wonder what you're at!
+
+There were three things that she had to do before she could go into the
+garden, and she was quite sure of all of them:
+1. find the key to the little door in the hall, which was too small,
+2. drink from the bottle that was marked with the words in the label,
+- eat the little cake that said EAT ME in currants on the top of it.
+
+She read the label of the bottle, and it said in large capital letters:
+    DRINK ME
+which was a very nice thing to find on a bottle, but she wasn't sure.
+";
+
+    fn reflow(text: &str) -> Vec<String> {
+        let mut text_vec: Vec<char> = text.chars().collect();
+        reflow_paragraphs(& mut text_vec);
+        text_vec.iter().collect::<String>().lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn hard_wrapped_prose_is_joined() {
+        let lines = reflow(ALICE);
+        assert!(lines.contains(&"Alice was beginning to get very tired of sitting by her sister on the \
+                                 bank, and of having nothing to do: once or twice she had peeped into the \
+                                 book her sister was reading, but it had no pictures or conversations in \
+                                 it, \"and what is the use of a book,\" thought Alice \"without pictures or \
+                                 conversations?\"".to_string()));
+        // The reflow doesn't move the chars.
+        assert_eq!(lines.join("\n").len() + 1, ALICE.len());
+        assert!(lines.contains(&"CHAPTER I.".to_string()));
+    }
+
+    #[test]
+    fn verse_is_kept() {
+        let lines = reflow(ALICE);
+        for line in ["  \"How doth the little crocodile", "    Improve his shining tail,",
+                     "Twinkle, twinkle, little bat!", "How I wonder what you're at!"] {
+            assert!(lines.contains(&line.to_string()), "{}", line);
+        }
+    }
+
+    #[test]
+    fn list_items_are_kept() {
+        let lines = reflow(ALICE);
+        assert!(lines.contains(&"There were three things that she had to do before she could go into the \
+                                 garden, and she was quite sure of all of them:".to_string()));
+        for line in ["1. find the key to the little door in the hall, which was too small,",
+                     "2. drink from the bottle that was marked with the words in the label,",
+                     "- eat the little cake that said EAT ME in currants on the top of it."] {
+            assert!(lines.contains(&line.to_string()), "{}", line);
+        }
+    }
+
+    #[test]
+    fn indented_lines_are_kept() {
+        let lines = reflow(ALICE);
+        assert!(lines.contains(&"    DRINK ME".to_string()));
+        assert!(lines.contains(&"She read the label of the bottle, and it said in large capital letters:".to_string()));
+    }
+
+    #[test]
+    fn short_lines_are_not_reflowed() {
+        let text = "one\ntwo\nthree\n";
+        assert_eq!(reflow(text), ["one", "two", "three"]);
+    }
+}
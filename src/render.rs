@@ -0,0 +1,154 @@
+// The drawing of the pages on the terminal, the text with the fixation points,
+// the styles of the markup, the matches of the search and the reading guide.
+
+use crossterm::{style::Colors, Result};
+use less_fp::fixation_mask;
+
+use crate::guide::GuideStep;
+use crate::layout::{char_cells, page_rows, shown_chars};
+use crate::search::SearchMode;
+use crate::settings::Settings;
+use crate::style::StyleAt;
+use crate::backend::{CellStyle, Terminal};
+use crate::theme::Theme;
+use crate::viewer::Viewer;
+
+/// For each char of the page, if it is in bold, the same rule as the export,
+/// or none in bold when the fixation points are hidden.
+fn page_fixation_mask(p_buf: &[char], settings: &Settings) -> Vec<bool> {
+    if settings.no_fixation {
+        vec![false; p_buf.len()]
+    } else {
+        fixation_mask(p_buf, settings.fixation)
+    }
+}
+
+/// Draws the current page of the viewer, from its state.
+pub fn render(term: & mut dyn Terminal, viewer: &Viewer) -> Result<()> {
+    let (_page_num, page) = viewer.pages_vec.get_curr_page();
+    let p_buf = viewer.page_text();
+    let text_area = &viewer.text_area;
+    let theme = &viewer.settings.theme;
+    let bold_mask = page_fixation_mask(p_buf, &viewer.settings);
+
+    term.clear(theme.text)?;
+
+    // Prints in bold and normal, the text on the terminal, row by row inside the text area.
+    let mut line_num = page.start_line_num;
+    let mut mid_line = page.starts_mid_line;
+    for (row, (row_start, row_stop)) in page_rows(p_buf, text_area).into_iter().enumerate() {
+        term.move_to(text_area.left_column, row as u16)?;
+
+        if text_area.gutter_width > 0 {
+            // The line number, or a continuation marker for a wrapped line.
+            let num_width = text_area.gutter_width as usize - 1;
+            let gutter = if mid_line {
+                format!("{:>num_width$} ", "↪")
+            } else {
+                format!("{:>num_width$} ", line_num)
+            };
+            term.print(&gutter, CellStyle::plain(theme.gutter))?;
+        }
+        mid_line = p_buf[row_stop] != '\n';
+        if !mid_line {
+            line_num += 1;
+        }
+
+        print_row(term, viewer, row_start, row_stop, &bold_mask, None)?;
+    }
+    match viewer.search_mode {
+        SearchMode::NotInMode => (),
+        SearchMode::EnteringSearchString => {
+            let string_out = "/ ".to_string() + &viewer.search_string;
+            term.move_to(0, text_area.height - 1)?;
+            term.print(&string_out, CellStyle::plain(theme.prompt))?;
+            },
+        SearchMode::BrowsingInSearch => (),
+    }
+
+    Ok(())
+}
+
+/// Prints the chars of a row, from the cursor position, the chars in the
+/// highlight of the reading guide are shown in its colors.
+fn print_row(term: & mut dyn Terminal, viewer: &Viewer, row_start: usize, row_stop: usize, bold_mask: &[bool],
+             highlight: Option<(usize, usize)>) -> Result<()> {
+    let (_page_num, page) = viewer.pages_vec.get_curr_page();
+    let p_buf = viewer.page_text();
+    let text_area = &viewer.text_area;
+    let theme = &viewer.settings.theme;
+    let global_start_pos = page.global_start_char_pos;
+    let mut column = 0_u16;
+    for (i, c) in p_buf.iter().enumerate().take(row_stop + 1).skip(row_start) {
+        let (shown, is_escaped) = shown_chars(*c, column, text_area.width);
+        column += char_cells(*c, column, text_area.width);
+        let mut flag_search_inside_word = false;
+        let mut flag_search_inside_current_word = false;
+        if let Some(ref search_tmp ) = viewer.search_opt {
+            flag_search_inside_word = search_tmp.is_inside_word(global_start_pos + i); 
+            flag_search_inside_current_word = search_tmp.is_inside_current_word(global_start_pos + i); 
+        }
+        let flag_inside_guide = highlight.is_some_and(|(start, end)| i >= start && i < end);
+
+        if *c == '\n' {
+            // The row ends here.
+        } else if flag_inside_guide {
+            let bold = bold_mask[i];
+            term.print(&shown, CellStyle { bold, ..CellStyle::plain(theme.guide) })?;
+        } else if flag_search_inside_current_word {
+            term.print(&shown, CellStyle::bold(theme.current_match))?;
+        } else if flag_search_inside_word {
+            term.print(&shown, CellStyle::plain(theme.search_match))?;
+        } else if is_escaped {
+            term.print(&shown, CellStyle { reverse: true, ..CellStyle::plain(theme.text) })?;
+        } else {
+            // The colors and attributes of the escape sequences or of the markup are merged with the fixation points.
+            let text_style = viewer.styles.style_at(global_start_pos + i).copied().unwrap_or_default();
+            let mut colors = Colors::new(text_style.fg.unwrap_or(theme.text.foreground.unwrap()),
+                                         text_style.bg.unwrap_or(theme.text.background.unwrap()));
+            if text_style.reverse {
+                colors = Colors::new(colors.background.unwrap(), colors.foreground.unwrap());
+            }
+            term.print(&shown, CellStyle {
+                colors,
+                bold: text_style.bold || (!text_style.no_fixation && bold_mask[i]),
+                italic: text_style.italic,
+                underline: text_style.underline,
+                reverse: false,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints again the rows of the page with the chars of the step of the reading
+/// guide, highlighted, or without the highlight when the guide moves away.
+pub fn print_guide(term: & mut dyn Terminal, viewer: &Viewer, step: &GuideStep, highlight: bool) -> Result<()> {
+    let p_buf = viewer.page_text();
+    let text_area = &viewer.text_area;
+    let bold_mask = page_fixation_mask(p_buf, &viewer.settings);
+    for (row, (row_start, row_stop)) in page_rows(p_buf, text_area).into_iter().enumerate() {
+        if row_stop >= step.start && row_start < step.end {
+            term.move_to(text_area.left_column + text_area.gutter_width, row as u16)?;
+            print_row(term, viewer, row_start, row_stop, &bold_mask, highlight.then_some((step.start, step.end)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints a message in the last row of the terminal, until the next key.
+pub fn print_message(term: & mut dyn Terminal, message: &str, theme: &Theme) -> Result<()> {
+    let (len_col, len_row) = term.size()?;
+    let message: String = message.chars().take(len_col as usize).collect();
+    term.move_to(0, len_row.saturating_sub(1))?;
+    term.print(&message, CellStyle::plain(theme.prompt))?;
+    term.clear_until_new_line(theme.text)
+}
+
+/// Prints the status at the right of the last row.
+pub fn print_status(term: & mut dyn Terminal, status: &str, theme: &Theme) -> Result<()> {
+    let (len_col, len_row) = term.size()?;
+    let status: String = status.chars().take(len_col as usize).collect();
+    term.move_to(len_col - status.chars().count() as u16, len_row.saturating_sub(1))?;
+    term.print(&status, CellStyle::plain(theme.prompt))
+}
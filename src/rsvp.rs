@@ -0,0 +1,203 @@
+// The RSVP (rapid serial visual presentation) view, the words are shown one at
+// a time at the same place of the screen, at the speed of the pacing mode. The
+// fixation letter of each word, the optimal recognition point (ORP), is always
+// at the same column and highlighted, so the eyes don't move.
+
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    event::{Event, KeyCode, KeyModifiers},
+    Result,
+};
+
+use crate::guide::word_weight;
+use crate::keymap::{Action, KeyMap};
+use crate::pacing::Pacing;
+use crate::backend::{CellStyle, Terminal};
+use crate::theme::Theme;
+use crate::toc::print_row;
+use less_fp::{tokenize, FixationStrategy};
+
+/// A word shown in the view, with the punctuation around it, ex: "(end)."
+struct Frame {
+    start: usize,
+    end: usize,
+    // The fixation points, from the start of the first word to the fixation letter.
+    word_start: usize,
+    orp: usize,
+    // The time to read it, in words.
+    weight: u32,
+}
+
+/// The frames of the text, the chars between the white spaces, with the
+/// fixation letter of their first word. A word joined by punctuation to the
+/// previous one, ex: the "t" of "don't", is in the same frame.
+fn rsvp_frames(text_vec: &[char], fixation: FixationStrategy) -> Vec<Frame> {
+    let mut frames_vec: Vec<Frame> = Vec::new();
+    for word in tokenize(text_vec, fixation) {
+        let weight = word_weight(text_vec, word.end);
+        match frames_vec.last_mut() {
+            Some(frame) if word.start < frame.end => {
+                frame.weight = weight;
+                continue;
+            },
+            _ => (),
+        }
+        let start = text_vec[..word.start].iter().rposition(|c| c.is_whitespace()).map_or(0, |pos| pos + 1);
+        let end = text_vec[word.end..].iter().position(|c| c.is_whitespace()).map_or(text_vec.len(), |pos| word.end + pos);
+        // The words that start with a number are all in bold, the fixation letter is the last one.
+        let orp = word.bold_end() - 1;
+        frames_vec.push(Frame { start, end, word_start: word.start, orp, weight });
+    }
+    frames_vec
+}
+
+/// Shows the words of the text, from the word at the position, and returns the
+/// global position of the word shown when the view is closed.
+pub fn show_rsvp(term: & mut dyn Terminal, text_vec: &[char], start_pos: usize, fixation: FixationStrategy, pacing: & mut Pacing,
+                 theme: &Theme, keymap: &KeyMap) -> Result<usize> {
+    let frames_vec = rsvp_frames(text_vec, fixation);
+    if frames_vec.is_empty() {
+        return Ok(start_pos);
+    }
+    let mut index = frames_vec.partition_point(|frame| frame.end <= start_pos).min(frames_vec.len() - 1);
+    let mut running = true;
+    let mut frame_start = Instant::now();
+    let mut redraw_all = true;
+    loop {
+        let (len_col, len_row) = term.size()?;
+        // The fixation letter is at the center of the screen, with a marker above and below.
+        let orp_column = len_col / 2;
+        let row = len_row.saturating_sub(1) / 2;
+        if redraw_all {
+            term.clear(theme.text)?;
+            for marker_row in [row.saturating_sub(1), row + 1] {
+                term.move_to(orp_column, marker_row)?;
+                term.print("│", CellStyle::plain(theme.gutter))?;
+            }
+            print_row(term, len_row.saturating_sub(1), "Space: pause   +, -: speed   Left, Right: word   Esc: back",
+                      len_col, theme.prompt)?;
+            redraw_all = false;
+        }
+        print_frame(term, text_vec, &frames_vec[index], orp_column, row, len_col, theme)?;
+        let symbol = if running { '▶' } else { '‖' };
+        let status = format!(" {} {:>4} wpm {:>3}% ", symbol, pacing.wpm, (index + 1) * 100 / frames_vec.len());
+        let status: String = status.chars().take(len_col as usize).collect();
+        term.move_to(len_col - status.chars().count() as u16, len_row.saturating_sub(1))?;
+        term.print(&status, CellStyle::plain(theme.prompt))?;
+
+        // The next word is shown when the time of the word is over, and the view pauses at the last one.
+        if running {
+            let duration = Duration::from_secs_f64(frames_vec[index].weight as f64 * 60.0 / pacing.wpm as f64);
+            let time_left = (frame_start + duration).saturating_duration_since(Instant::now());
+            if !term.poll(time_left)? {
+                if index + 1 < frames_vec.len() {
+                    index += 1;
+                } else {
+                    running = false;
+                }
+                frame_start = Instant::now();
+                continue;
+            }
+        }
+
+        let event = term.read()?;
+        let action = keymap.action(&event);
+        if let Event::Resize(_, _) = event {
+            redraw_all = true;
+            continue;
+        }
+        if let Event::Key(key_event) = event {
+            if    matches!(action, Some(Action::Quit | Action::ToggleRsvp))
+               || key_event.code == KeyCode::Esc
+               || (key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL) {
+                return Ok(frames_vec[index].start);
+            }
+            match (action, key_event.code) {
+                (Some(Action::TogglePacing), _) => running = !running,
+                (Some(Action::Faster), _) => pacing.faster(),
+                (Some(Action::Slower), _) => pacing.slower(),
+                (Some(Action::PrevPage), _) | (_, KeyCode::Left) => index = index.saturating_sub(1),
+                (Some(Action::NextPage), _) | (_, KeyCode::Right) => index = (index + 1).min(frames_vec.len() - 1),
+                _ => (),
+            }
+            frame_start = Instant::now();
+        }
+    }
+}
+
+/// Prints the frame on the row, with its fixation letter at the column.
+fn print_frame(term: & mut dyn Terminal, text_vec: &[char], frame: &Frame, orp_column: u16, row: u16, len_col: u16,
+               theme: &Theme) -> Result<()> {
+    term.move_to(0, row)?;
+    term.clear_until_new_line(theme.text)?;
+    // The chars before the fixation letter that don't fit at the left are cut.
+    let skipped = (frame.orp - frame.start).saturating_sub(orp_column as usize);
+    let first_column = orp_column as usize - (frame.orp - frame.start - skipped);
+    term.move_to(first_column as u16, row)?;
+    for (i, c) in text_vec.iter().enumerate().take(frame.end).skip(frame.start + skipped).take(len_col as usize - first_column) {
+        let shown = if c.is_control() { '?' } else { *c };
+        let style = if i == frame.orp {
+            CellStyle::bold(theme.guide)
+        } else if i >= frame.word_start && i < frame.orp {
+            CellStyle::bold(theme.text)
+        } else {
+            CellStyle::plain(theme.text)
+        };
+        term.print(&shown.to_string(), style)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The frames of the text, as their chars, the offset of their fixation letter and their weight.
+    fn frames(text: &str, fixation: FixationStrategy) -> Vec<(String, usize, u32)> {
+        let text_vec: Vec<char> = text.chars().collect();
+        rsvp_frames(&text_vec, fixation).iter()
+                                        .map(|frame| (text_vec[frame.start..frame.end].iter().collect(),
+                                                      frame.orp - frame.start,
+                                                      frame.weight))
+                                        .collect()
+    }
+
+    #[test]
+    fn fixation_letter_of_short_and_long_words() {
+        assert_eq!(frames("a to the reading extraordinary", FixationStrategy::Half),
+                   [("a".to_string(), 0, 1), ("to".to_string(), 0, 1), ("the".to_string(), 0, 1),
+                    ("reading".to_string(), 3, 1), ("extraordinary".to_string(), 6, 1)]);
+        assert_eq!(frames("a reading", FixationStrategy::FirstLetter),
+                   [("a".to_string(), 0, 1), ("reading".to_string(), 0, 1)]);
+    }
+
+    #[test]
+    fn fixation_letter_of_number_words() {
+        // The words that start with a number are all in bold, the fixation letter is the last one.
+        assert_eq!(frames("42 7th 3.14", FixationStrategy::Half),
+                   [("42".to_string(), 1, 1), ("7th".to_string(), 2, 1), ("3.14".to_string(), 0, 1)]);
+    }
+
+    #[test]
+    fn pauses_of_the_punctuation() {
+        let frames_vec = frames("One, two. \"Three.\" (four) five; six... e.g. seven", FixationStrategy::Half);
+        let weights: Vec<(&str, u32)> = frames_vec.iter().map(|(frame, _, weight)| (frame.as_str(), *weight)).collect();
+        assert_eq!(weights, [("One,", 2), ("two.", 3), ("\"Three.\"", 3), ("(four)", 1), ("five;", 2), ("six...", 3),
+                             ("e.g.", 3), ("seven", 1)]);
+        // The fixation letter of the word after the punctuation.
+        assert_eq!(frames_vec[2].1, 3);
+        assert_eq!(frames_vec[3].1, 2);
+    }
+
+    #[test]
+    fn joined_words_are_one_frame() {
+        assert_eq!(frames("don't stop-here x", FixationStrategy::Half),
+                   [("don't".to_string(), 0, 1), ("stop-here".to_string(), 1, 1), ("x".to_string(), 0, 1)]);
+    }
+
+    #[test]
+    fn no_frames_of_an_empty_text() {
+        assert!(frames(" \n ", FixationStrategy::Half).is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+// The search of a string in the text, the positions of its occurrences and
+// the occurrence that is shown.
+
+use crate::config::SearchOptions;
+use crate::string_utils::{StringUtils, StringUtilsVecCharsV2};
+
+pub struct TextPos {
+    pub start_pos: usize,
+    pub end_pos: usize,
+}
+
+pub struct Search {
+    curr_pos: usize,
+    // After the last occurrence goes to the first one, and vice versa.
+    wrap_around: bool,
+    text_pos_vec: Vec<TextPos>,
+}
+
+impl Search {
+    pub fn find(global_text: &[char], search_string: &str, search_options: &SearchOptions) -> Option<Self> {
+        let mut pattern_vec = search_string.get_vec_chars();
+        let ocurrencies = if search_options.ignore_case {
+            // Char by char, so that the positions in the text don't change.
+            let to_lower = |c: &char| c.to_lowercase().next().unwrap_or(*c);
+            let lower_text_vec: Vec<char> = global_text.iter().map(to_lower).collect();
+            pattern_vec = pattern_vec.iter().map(to_lower).collect();
+            lower_text_vec.find_vec_all(&pattern_vec)
+        } else {
+            global_text.to_vec().find_vec_all(&pattern_vec)
+        };
+        if ocurrencies.is_empty() {
+            return None;
+        }
+        let mut text_pos_vec: Vec<TextPos> = Vec::new();
+        for start_pos in ocurrencies {
+            text_pos_vec.push(
+                TextPos { start_pos, end_pos: start_pos + pattern_vec.len() - 1});
+        }
+        Some(
+            Search {
+                curr_pos: 0,
+                wrap_around: search_options.wrap_around,
+                text_pos_vec
+                }
+            )
+    }
+
+    pub fn get_curr_pos(&self) -> (usize, &TextPos) {
+        (self.curr_pos, &self.text_pos_vec[self.curr_pos])
+    }
+
+    pub fn next_pos(& mut self) -> bool {
+        if self.curr_pos < self.text_pos_vec.len() - 1 {
+            self.curr_pos += 1;
+            return true;
+        } else if self.wrap_around && self.text_pos_vec.len() > 1 {
+            self.curr_pos = 0;
+            return true;
+        }
+        false
+    }
+
+    pub fn prev_pos(& mut self) -> bool {
+        if self.curr_pos > 0 {
+            self.curr_pos -= 1;
+            return true;
+        } else if self.wrap_around && self.text_pos_vec.len() > 1 {
+            self.curr_pos = self.text_pos_vec.len() - 1;
+            return true;
+        }
+        false
+    }
+
+    // In here I have to calculate the forward distance from the current position
+    // for all occurrences and get the minimal value.
+    // If it didn't find, it goes to the first one.
+    pub fn find_next_nearest_pos(& mut self, text_vec: &[char], global_curr_page_start_pos: usize) -> usize {
+        let mut lowest_distance = text_vec.len() as i32;
+        let mut last_word_index = 0_usize;
+
+        // Search's next in front of current start of current page start position, in reverse, from the end to the current position.
+        for (word_index, text_pos) in self.text_pos_vec.iter().rev().enumerate() {
+           let delta = text_pos.start_pos as i32 - global_curr_page_start_pos as i32;
+           if delta >= 0 && delta < lowest_distance {
+               lowest_distance = delta;
+               last_word_index = self.text_pos_vec.len() - 1 - word_index;
+           }
+        }
+
+        if lowest_distance < text_vec.len() as i32 {
+            // Found word occurrence position in front of the start of current page.
+            self.curr_pos = last_word_index;
+            self.text_pos_vec[last_word_index].start_pos
+        } else {
+            // Returns the global position of first occurrence in the text file.
+            self.curr_pos = 0;
+            self.text_pos_vec[0].start_pos
+        }           
+    }
+
+    pub fn is_inside_word(& self, global_pos_of_char: usize) -> bool {
+        for text_pos in self.text_pos_vec.iter() {
+            if    global_pos_of_char >= text_pos.start_pos
+               && global_pos_of_char <= text_pos.end_pos {
+                   return true;
+               }
+        }
+        false
+    }
+
+    pub fn is_inside_current_word(& self, global_pos_of_char: usize) -> bool {
+        if    global_pos_of_char >= self.text_pos_vec[self.curr_pos].start_pos
+            && global_pos_of_char <= self.text_pos_vec[self.curr_pos].end_pos {
+                return true;
+            }
+        false
+    }
+
+}
+
+pub enum SearchMode {
+    NotInMode,
+    EnteringSearchString,
+    BrowsingInSearch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn find_the_occurrences() {
+        let text_vec = chars("Rabbit rabbit RABBIT");
+        let search = Search::find(&text_vec, "rabbit", &SearchOptions::default()).unwrap();
+        let (index, text_pos) = search.get_curr_pos();
+        assert_eq!((index, text_pos.start_pos, text_pos.end_pos), (0, 7, 12));
+        assert!(search.is_inside_word(12));
+        assert!(!search.is_inside_word(13));
+
+        let ignore_case = SearchOptions { ignore_case: true, wrap_around: false };
+        let search = Search::find(&text_vec, "rabbit", &ignore_case).unwrap();
+        assert_eq!(search.text_pos_vec.len(), 3);
+        assert!(Search::find(&text_vec, "fox", &ignore_case).is_none());
+    }
+
+    #[test]
+    fn next_and_prev_occurrence() {
+        let text_vec = chars("a b a b a");
+        let mut search = Search::find(&text_vec, "a", &SearchOptions::default()).unwrap();
+        assert!(!search.prev_pos());
+        assert!(search.next_pos() && search.next_pos());
+        assert!(!search.next_pos());
+        assert!(search.is_inside_current_word(8));
+
+        let mut search = Search::find(&text_vec, "a", &SearchOptions { ignore_case: false, wrap_around: true }).unwrap();
+        assert!(search.prev_pos());
+        assert_eq!(search.get_curr_pos().0, 2);
+        assert!(search.next_pos());
+        assert_eq!(search.get_curr_pos().0, 0);
+    }
+
+    #[test]
+    fn nearest_occurrence_after_the_page() {
+        let text_vec = chars("a b a b a");
+        let mut search = Search::find(&text_vec, "a", &SearchOptions::default()).unwrap();
+        assert_eq!(search.find_next_nearest_pos(&text_vec, 3), 4);
+        assert_eq!(search.get_curr_pos().0, 1);
+        // After the last one, it goes to the first one.
+        let mut search = Search::find(&text_vec, "b", &SearchOptions::default()).unwrap();
+        assert_eq!(search.find_next_nearest_pos(&text_vec, 4), 6);
+        assert_eq!(search.find_next_nearest_pos(&text_vec, 7), 2);
+    }
+}
@@ -0,0 +1,25 @@
+// The settings of the reading, from the config file and the command line
+// options, shared by the viewer, the drawing of the pages and the views.
+
+use less_fp::FixationStrategy;
+
+use crate::config::SearchOptions;
+use crate::keymap::KeyMap;
+use crate::theme::Theme;
+
+/// The settings from the config file, only the fixation points can be hidden while reading.
+pub struct Settings {
+    pub theme: Theme,
+    pub fixation: FixationStrategy,
+    // The fixation points are hidden, to compare the reading speed without them.
+    pub no_fixation: bool,
+    pub search_options: SearchOptions,
+    pub keymap: KeyMap,
+    // Don't use the alternate screen.
+    pub no_init: bool,
+    // Open the file at the last reading position.
+    pub resume: bool,
+    // The speed of the pacing mode, and if it starts when the file is opened.
+    pub wpm: u16,
+    pub auto_advance: bool,
+}
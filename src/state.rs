@@ -0,0 +1,247 @@
+// The state that persists across the sessions, the marks, the last reading
+// position and the reading statistics of each file, saved in
+// $XDG_STATE_HOME/less_fp/state.json or in ~/.local/state/less_fp/state.json.
+// The files are identified by their path and the hash of their text, so that
+// the saved positions aren't used in a file whose text changed.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{Session, MAX_SESSIONS};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct State {
+    files: BTreeMap<String, FileState>,
+}
+
+/// The state of one file.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct FileState {
+    // The global char positions of the marks, by their letter.
+    pub marks: BTreeMap<char, usize>,
+    // The global char position of the start of the page shown on exit.
+    pub last_pos: Option<usize>,
+    // The reading sessions, the oldest first.
+    pub sessions: Vec<Session>,
+}
+
+/// The directory of the state file, following the XDG Base Directory specification.
+pub fn state_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("less_fp")),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state").join("less_fp")),
+    }
+}
+
+/// The key of the file in the state file, its path and the hash of its text.
+pub fn file_key(file: Option<&Path>, text_vec: &[char]) -> String {
+    format!("{}#{:016x}", file_path_key(file), fnv1a_hash(text_vec))
+}
+
+/// The path part of the key of the file, its canonical path, or "-" for the standard input.
+pub fn file_path_key(file: Option<&Path>) -> String {
+    match file {
+        Some(file) => fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())
+                                            .to_string_lossy()
+                                            .into_owned(),
+        None => "-".to_string(),
+    }
+}
+
+/// The states of all the files, by their key.
+pub fn load_files() -> BTreeMap<String, FileState> {
+    match state_dir() {
+        Some(dir) => read_state(&dir.join("state.json")).files,
+        None => BTreeMap::new(),
+    }
+}
+
+/// The 64 bits FNV-1a hash of the chars.
+fn fnv1a_hash(text_vec: &[char]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = FNV_OFFSET_BASIS;
+    for c in text_vec {
+        for byte in (*c as u32).to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// The persistent state of the file that is being read.
+pub struct Bookmarks {
+    state_path: Option<PathBuf>,
+    file_key: String,
+    pub file_state: FileState,
+}
+
+impl Bookmarks {
+    /// Reads the state of the file, a missing or invalid state file is an empty state.
+    pub fn load(file_key: String) -> Self {
+        let state_path = state_dir().map(|dir| dir.join("state.json"));
+        let file_state = state_path.as_deref()
+                                   .map(read_state)
+                                   .and_then(|mut state| state.files.remove(&file_key))
+                                   .unwrap_or_default();
+        Bookmarks { state_path, file_key, file_state }
+    }
+
+    /// The state of a file that isn't saved, for the tests.
+    #[cfg(test)]
+    pub fn unsaved() -> Self {
+        Bookmarks { state_path: None, file_key: String::new(), file_state: FileState::default() }
+    }
+
+    pub fn mark(&self, letter: char) -> Option<usize> {
+        self.file_state.marks.get(&letter).copied()
+    }
+
+    /// Sets the mark at the position and saves the state.
+    pub fn set_mark(& mut self, letter: char, global_pos: usize) -> io::Result<()> {
+        self.file_state.marks.insert(letter, global_pos);
+        self.save()
+    }
+
+    /// Sets the last reading position and saves the state.
+    pub fn set_last_pos(& mut self, global_pos: usize) -> io::Result<()> {
+        self.file_state.last_pos = Some(global_pos);
+        self.save()
+    }
+
+    /// Adds the reading session and saves the state.
+    pub fn add_session(& mut self, session: Session) -> io::Result<()> {
+        let sessions = & mut self.file_state.sessions;
+        sessions.push(session);
+        if sessions.len() > MAX_SESSIONS {
+            sessions.drain(..sessions.len() - MAX_SESSIONS);
+        }
+        self.save()
+    }
+
+    /// Writes the state of the file, the states of the other files are read
+    /// again, because they could have been changed by another session.
+    pub fn save(&self) -> io::Result<()> {
+        let state_path = match self.state_path.as_deref() {
+            Some(state_path) => state_path,
+            None => return Ok(()),
+        };
+        let mut state = read_state(state_path);
+        state.files.insert(self.file_key.clone(), self.file_state.clone());
+        if let Some(dir) = state_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Written to a temporary file and renamed, so the state file is never left half written.
+        let tmp_path = state_path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, state_path)
+    }
+}
+
+fn read_state(state_path: &Path) -> State {
+    fs::read_to_string(state_path).ok()
+                                  .and_then(|json| serde_json::from_str(&json).ok())
+                                  .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // The tests that change XDG_STATE_HOME run one at a time.
+    static STATE_HOME: Mutex<()> = Mutex::new(());
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    /// Runs the test with XDG_STATE_HOME in a new temporary directory.
+    fn with_state_home(name: &str, test: impl FnOnce(&Path)) {
+        let _lock = STATE_HOME.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = env::temp_dir().join(format!("less_fp_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        env::set_var("XDG_STATE_HOME", &dir);
+        test(&dir);
+        env::remove_var("XDG_STATE_HOME");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fnv1a_of_the_chars() {
+        assert_eq!(fnv1a_hash(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_hash(&chars("text")), fnv1a_hash(&chars("text")));
+        assert_ne!(fnv1a_hash(&chars("text")), fnv1a_hash(&chars("next")));
+        // All the bytes of the chars are hashed.
+        assert_ne!(fnv1a_hash(&chars("a")), fnv1a_hash(&chars("\u{10061}")));
+    }
+
+    #[test]
+    fn file_key_is_the_path_and_the_hash_of_the_text() {
+        let text_vec = chars("Some text.\n");
+        assert_eq!(file_key(None, &text_vec), format!("-#{:016x}", fnv1a_hash(&text_vec)));
+
+        let file = Path::new("/no/such/dir/file.txt");
+        assert_eq!(file_key(Some(file), &text_vec), format!("/no/such/dir/file.txt#{:016x}", fnv1a_hash(&text_vec)));
+        assert_ne!(file_key(Some(file), &text_vec), file_key(Some(file), &chars("Other text.\n")));
+        assert_ne!(file_key(Some(file), &text_vec), file_key(Some(Path::new("/no/such/dir/other.txt")), &text_vec));
+    }
+
+    #[test]
+    fn state_dir_follows_xdg_state_home() {
+        with_state_home("dir", |dir| {
+            assert_eq!(state_dir(), Some(dir.join("less_fp")));
+        });
+    }
+
+    #[test]
+    fn marks_and_last_pos_round_trip() {
+        with_state_home("round_trip", |dir| {
+            let key = file_key(None, &chars("Some text.\n"));
+            let mut bookmarks = Bookmarks::load(key.clone());
+            assert!(bookmarks.file_state.marks.is_empty());
+            assert_eq!(bookmarks.file_state.last_pos, None);
+
+            bookmarks.set_mark('a', 5).unwrap();
+            bookmarks.set_mark('b', 9).unwrap();
+            bookmarks.set_last_pos(7).unwrap();
+            assert!(dir.join("less_fp").join("state.json").is_file());
+
+            let bookmarks = Bookmarks::load(key.clone());
+            assert_eq!(bookmarks.mark('a'), Some(5));
+            assert_eq!(bookmarks.mark('b'), Some(9));
+            assert_eq!(bookmarks.mark('c'), None);
+            assert_eq!(bookmarks.file_state.last_pos, Some(7));
+            assert_eq!(load_files().keys().collect::<Vec<_>>(), [&key]);
+
+            // Another text has its own state, and saving it keeps the first one.
+            let other_key = file_key(None, &chars("Other text.\n"));
+            let mut other = Bookmarks::load(other_key.clone());
+            assert_eq!(other.mark('a'), None);
+            other.set_mark('a', 1).unwrap();
+            assert_eq!(Bookmarks::load(key).mark('a'), Some(5));
+            assert_eq!(Bookmarks::load(other_key).mark('a'), Some(1));
+        });
+    }
+
+    #[test]
+    fn invalid_state_file_is_an_empty_state() {
+        with_state_home("invalid", |dir| {
+            fs::create_dir_all(dir.join("less_fp")).unwrap();
+            fs::write(dir.join("less_fp").join("state.json"), "{ not json").unwrap();
+            let mut bookmarks = Bookmarks::load("-#0".to_string());
+            assert_eq!(bookmarks.file_state.last_pos, None);
+            bookmarks.set_last_pos(3).unwrap();
+            assert_eq!(Bookmarks::load("-#0".to_string()).file_state.last_pos, Some(3));
+        });
+    }
+}
@@ -0,0 +1,381 @@
+// The reading statistics, to measure if the fixation points help. The time
+// that each page is shown is taken with its number of words, for the speed
+// in words per minute, with and without the fixation points. The sessions
+// are saved with the state of each file, and summarized by the stats screen
+// and by the option --stats.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crossterm::{event::Event, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::state;
+use crate::backend::Terminal;
+use crate::theme::Theme;
+use crate::toc::print_row;
+
+/// The pages shown for less time were skipped, and the pages shown for more
+/// time weren't being read, ex: the reader went away. They aren't counted.
+const MIN_PAGE_SECS: f64 = 2.0;
+const MAX_PAGE_SECS: f64 = 600.0;
+/// The sessions kept for each file, the oldest are removed.
+pub const MAX_SESSIONS: usize = 100;
+/// The sessions listed in the stats screen.
+const NUM_LAST_SESSIONS: usize = 5;
+
+/// A page that was read.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PageRead {
+    // The global position of the start of the page.
+    pub start_pos: usize,
+    pub words: usize,
+    pub secs: f64,
+    // The page was read without the fixation points.
+    pub no_fixation: bool,
+}
+
+/// The pages read while the file was open.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Session {
+    // The start, in seconds since the Unix epoch.
+    pub start_time: u64,
+    // The time that the file was open, in seconds.
+    pub duration_secs: f64,
+    pub pages: Vec<PageRead>,
+}
+
+/// The totals of some pages read.
+#[derive(Serialize, Default)]
+pub struct Speed {
+    pub pages: usize,
+    pub words: usize,
+    pub reading_secs: f64,
+    pub wpm: f64,
+}
+
+impl Speed {
+    fn add(& mut self, page: &PageRead) {
+        self.pages += 1;
+        self.words += page.words;
+        self.reading_secs += page.secs;
+        if self.reading_secs > 0.0 {
+            self.wpm = self.words as f64 * 60.0 / self.reading_secs;
+        }
+    }
+}
+
+/// The totals of the pages read in some sessions, and of the pages read with
+/// and without the fixation points.
+#[derive(Serialize, Default)]
+pub struct Summary {
+    pub sessions: usize,
+    #[serde(flatten)]
+    pub total: Speed,
+    pub fixation: Speed,
+    pub no_fixation: Speed,
+}
+
+impl Summary {
+    pub fn of<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> Self {
+        let mut summary = Summary::default();
+        for session in sessions {
+            summary.sessions += 1;
+            for page in session.pages.iter() {
+                summary.total.add(page);
+                if page.no_fixation {
+                    summary.no_fixation.add(page);
+                } else {
+                    summary.fixation.add(page);
+                }
+            }
+        }
+        summary
+    }
+
+    /// One line, ex: "3 sessions, 42 pages in 1 h 5 min, 12000 words at 185 wpm",
+    /// without the sessions for one session.
+    fn line(&self) -> String {
+        let sessions = if self.sessions == 1 { String::new() } else { format!("{} sessions, ", self.sessions) };
+        format!("{}{} in {}, {} words at {:.0} wpm",
+                sessions, format_pages(self.total.pages), format_duration(self.total.reading_secs),
+                self.total.words, self.total.wpm)
+    }
+
+    /// The speeds with and without the fixation points, ex: "with fixation
+    /// points 190 wpm in 30 pages, without 175 wpm in 12 pages", or None when
+    /// all the pages were read in the same mode.
+    fn comparison_line(&self) -> Option<String> {
+        if self.fixation.pages == 0 || self.no_fixation.pages == 0 {
+            return None;
+        }
+        Some(format!("with fixation points {:.0} wpm in {}, without {:.0} wpm in {}",
+                     self.fixation.wpm, format_pages(self.fixation.pages),
+                     self.no_fixation.wpm, format_pages(self.no_fixation.pages)))
+    }
+
+    /// The line, and the comparison line when there is one.
+    fn lines(&self) -> Vec<String> {
+        let mut lines_vec = vec![self.line()];
+        lines_vec.extend(self.comparison_line());
+        lines_vec
+    }
+}
+
+/// The time of the pages of the session that is being read.
+pub struct ReadingStats {
+    pub session: Session,
+    started: Instant,
+    // The page that is shown, as the global position of its start, its words,
+    // if the fixation points are hidden and when it was shown.
+    page_shown: Option<(usize, usize, bool, Instant)>,
+}
+
+impl ReadingStats {
+    pub fn start() -> Self {
+        let start_time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+        ReadingStats {
+            session: Session { start_time, ..Session::default() },
+            started: Instant::now(),
+            page_shown: None,
+        }
+    }
+
+    /// The page is on the screen, its time starts when it wasn't the page shown
+    /// before, or when the fixation points were hidden or shown.
+    pub fn page_shown(& mut self, start_pos: usize, words: usize, no_fixation: bool) {
+        match self.page_shown {
+            Some((shown_pos, _words, shown_no_fixation, _shown_at))
+                if shown_pos == start_pos && shown_no_fixation == no_fixation => (),
+            _ => {
+                self.page_left();
+                self.page_shown = Some((start_pos, words, no_fixation, Instant::now()));
+            },
+        }
+    }
+
+    /// The page isn't on the screen, ex: another screen is shown, its time is counted.
+    pub fn page_left(& mut self) {
+        if let Some((start_pos, words, no_fixation, shown_at)) = self.page_shown.take() {
+            let secs = shown_at.elapsed().as_secs_f64();
+            if (MIN_PAGE_SECS..=MAX_PAGE_SECS).contains(&secs) && words > 0 {
+                self.session.pages.push(PageRead { start_pos, words, secs, no_fixation });
+            }
+        }
+    }
+
+    /// The session, when the file is closed.
+    pub fn end_session(mut self) -> Session {
+        self.page_left();
+        self.session.duration_secs = self.started.elapsed().as_secs_f64();
+        self.session
+    }
+}
+
+/// Shows the statistics of the session and of the file, until a key is pressed.
+pub fn show_stats(term: & mut dyn Terminal, session: &Session, file_sessions: &[Session], theme: &Theme) -> Result<()> {
+    let mut lines_vec: Vec<String> = vec![String::new(), "This session".to_string()];
+    lines_vec.extend(Summary::of([session]).lines().iter().map(|line| format!("  {}", line)));
+    lines_vec.extend([String::new(), "This file".to_string()]);
+    lines_vec.extend(Summary::of(file_sessions.iter().chain([session])).lines().iter().map(|line| format!("  {}", line)));
+    lines_vec.extend([String::new(), "Last sessions".to_string()]);
+    for old_session in file_sessions.iter().rev().take(NUM_LAST_SESSIONS) {
+        lines_vec.push(format!("  {}  {}", format_date(old_session.start_time), Summary::of([old_session]).line()));
+    }
+    if file_sessions.is_empty() {
+        lines_vec.push("  None".to_string());
+    }
+
+    let (len_col, len_row) = term.size()?;
+    term.clear(theme.text)?;
+    print_row(term, 0, "Reading statistics", len_col, theme.prompt)?;
+    for (row, line) in lines_vec.iter().enumerate().take((len_row as usize).saturating_sub(2)) {
+        print_row(term, row as u16 + 1, line, len_col, theme.text)?;
+    }
+    print_row(term, len_row.saturating_sub(1), "Any key: back", len_col, theme.prompt)?;
+    // The screen is closed by a key, or by a resize.
+    while let Event::Mouse(_) = term.read()? {}
+    Ok(())
+}
+
+/// The summary of each file in the state file, or of the file, for the option
+/// --stats, in text or in JSON.
+pub fn print_stats(file: Option<&Path>, json: bool) {
+    // The sessions by the path of the file, a file whose text changed has many keys.
+    let mut sessions_by_path: BTreeMap<String, Vec<Session>> = BTreeMap::new();
+    for (file_key, file_state) in state::load_files() {
+        let path = file_key.rsplit_once('#').map_or(file_key.as_str(), |(path, _hash)| path).to_string();
+        sessions_by_path.entry(path).or_default().extend(file_state.sessions);
+    }
+    if let Some(file) = file {
+        let path = state::file_path_key(Some(file));
+        sessions_by_path.retain(|file_path, _sessions| *file_path == path);
+    }
+    sessions_by_path.retain(|_file_path, sessions| !sessions.is_empty());
+
+    let total = Summary::of(sessions_by_path.values().flatten());
+    if json {
+        #[derive(Serialize)]
+        struct FileSummary<'a> {
+            file: &'a str,
+            #[serde(flatten)]
+            summary: Summary,
+        }
+        #[derive(Serialize)]
+        struct Stats<'a> {
+            files: Vec<FileSummary<'a>>,
+            total: Summary,
+        }
+        let files = sessions_by_path.iter()
+                                    .map(|(file, sessions)| FileSummary { file, summary: Summary::of(sessions) })
+                                    .collect();
+        println!("{}", serde_json::to_string_pretty(&Stats { files, total }).unwrap_or_default());
+    } else if sessions_by_path.is_empty() {
+        println!("No reading sessions.");
+    } else {
+        for (file, sessions) in sessions_by_path.iter() {
+            println!("{}", file);
+            for line in Summary::of(sessions).lines() {
+                println!("  {}", line);
+            }
+        }
+        println!("Total");
+        for line in total.lines() {
+            println!("  {}", line);
+        }
+    }
+}
+
+/// The number of pages, ex: "1 page" or "42 pages".
+fn format_pages(pages: usize) -> String {
+    format!("{} page{}", pages, if pages == 1 { "" } else { "s" })
+}
+
+/// The time in hours and minutes, ex: "1 h 5 min", or in seconds when shorter than a minute.
+fn format_duration(secs: f64) -> String {
+    let minutes = (secs / 60.0).round() as u64;
+    if secs < 60.0 {
+        format!("{:.0} s", secs)
+    } else if minutes < 60 {
+        format!("{} min", minutes)
+    } else {
+        format!("{} h {} min", minutes / 60, minutes % 60)
+    }
+}
+
+/// The date and time in UTC, ex: "2022-06-04 18:30".
+fn format_date(unix_secs: u64) -> String {
+    // The days since the epoch to the civil date, from the algorithm of Howard Hinnant.
+    let days = (unix_secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    let secs_of_day = unix_secs % 86400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, secs_of_day / 3600, secs_of_day % 3600 / 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn page(words: usize, secs: f64, no_fixation: bool) -> PageRead {
+        PageRead { start_pos: 0, words, secs, no_fixation }
+    }
+
+    fn session(pages: Vec<PageRead>) -> Session {
+        Session { start_time: 0, duration_secs: 0.0, pages }
+    }
+
+    /// The stats with the page shown for the time.
+    fn shown_for(words: usize, secs: u64) -> ReadingStats {
+        let mut reading_stats = ReadingStats::start();
+        let shown_at = Instant::now().checked_sub(Duration::from_secs(secs)).unwrap();
+        reading_stats.page_shown = Some((0, words, false, shown_at));
+        reading_stats
+    }
+
+    #[test]
+    fn speed_of_the_pages() {
+        let sessions = [session(vec![page(300, 60.0, false), page(100, 60.0, true)]),
+                        session(vec![page(200, 60.0, false)])];
+        let summary = Summary::of(&sessions);
+        assert_eq!(summary.sessions, 2);
+        assert_eq!((summary.total.pages, summary.total.words), (3, 600));
+        assert_eq!(summary.total.wpm, 200.0);
+        assert_eq!(summary.fixation.wpm, 250.0);
+        assert_eq!(summary.no_fixation.wpm, 100.0);
+        assert_eq!(summary.lines(), ["2 sessions, 3 pages in 3 min, 600 words at 200 wpm",
+                                     "with fixation points 250 wpm in 2 pages, without 100 wpm in 1 page"]);
+    }
+
+    #[test]
+    fn speed_of_a_session_without_pages() {
+        let summary = Summary::of([&session(Vec::new())]);
+        assert_eq!(summary.total.wpm, 0.0);
+        assert_eq!(summary.lines(), ["0 pages in 0 s, 0 words at 0 wpm"]);
+        let summary = Summary::of([]);
+        assert_eq!(summary.total.wpm, 0.0);
+        // The pages of zero seconds aren't saved, but could be in an edited state file.
+        let summary = Summary::of([&session(vec![page(100, 0.0, false)])]);
+        assert!(summary.total.wpm.is_finite());
+        assert_eq!(summary.comparison_line(), None);
+    }
+
+    #[test]
+    fn format_the_dates() {
+        assert_eq!(format_date(0), "1970-01-01 00:00");
+        assert_eq!(format_date(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_date(1_654_367_400), "2022-06-04 18:30");
+        assert_eq!(format_date(1_704_067_199), "2023-12-31 23:59");
+    }
+
+    #[test]
+    fn format_the_durations() {
+        assert_eq!(format_duration(42.4), "42 s");
+        assert_eq!(format_duration(150.0), "3 min");
+        assert_eq!(format_duration(3900.0), "1 h 5 min");
+    }
+
+    #[test]
+    fn pages_read_are_counted() {
+        let mut reading_stats = shown_for(120, 30);
+        reading_stats.page_left();
+        assert_eq!(reading_stats.session.pages.len(), 1);
+        assert_eq!(reading_stats.session.pages[0].words, 120);
+        assert!(reading_stats.session.pages[0].secs >= 30.0);
+        // The page is counted once.
+        reading_stats.page_left();
+        assert_eq!(reading_stats.session.pages.len(), 1);
+    }
+
+    #[test]
+    fn skipped_and_abandoned_pages_are_not_counted() {
+        for (words, secs) in [(120, 0), (120, 1), (120, 601), (0, 30)] {
+            let mut reading_stats = shown_for(words, secs);
+            reading_stats.page_left();
+            assert!(reading_stats.session.pages.is_empty(), "{} words in {} s", words, secs);
+        }
+    }
+
+    #[test]
+    fn same_page_shown_again_keeps_its_time() {
+        let mut reading_stats = shown_for(120, 30);
+        reading_stats.page_shown(0, 120, false);
+        assert!(reading_stats.session.pages.is_empty());
+        // Hiding the fixation points starts the time of the page again.
+        reading_stats.page_shown(0, 120, true);
+        assert_eq!(reading_stats.session.pages.len(), 1);
+        assert!(!reading_stats.session.pages[0].no_fixation);
+        let session = reading_stats.end_session();
+        assert_eq!(session.pages.len(), 1);
+    }
+}
@@ -0,0 +1,54 @@
+// The styles of the chars of the text, from the ANSI escape sequences in the
+// raw control chars mode or from the markup of the rendered documents.
+
+use crossterm::style::Color;
+
+/// The colors and attributes of a run of chars.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct TextStyle {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+    // The fixation points aren't shown, ex: in the code blocks.
+    pub no_fixation: bool,
+}
+
+/// A style that applies to the chars from start_pos to end_pos, inclusive.
+pub struct StyleSpan {
+    pub start_pos: usize,
+    pub end_pos: usize,
+    pub style: TextStyle,
+}
+
+pub trait StyleAt {
+    fn style_at(&self, global_pos: usize) -> Option<&TextStyle>;
+}
+
+impl StyleAt for [StyleSpan] {
+    /// The spans are ordered and don't overlap, so a binary search is enough.
+    fn style_at(&self, global_pos: usize) -> Option<&TextStyle> {
+        let index = self.partition_point(|span| span.end_pos < global_pos);
+        match self.get(index) {
+            Some(span) if span.start_pos <= global_pos => Some(&span.style),
+            _ => None,
+        }
+    }
+}
+
+/// Adds the style of the char at the position, that follows the previous
+/// chars, extending the last span when it has the same style.
+pub fn push_char_style(spans_vec: & mut Vec<StyleSpan>, pos: usize, style: TextStyle) {
+    if style == TextStyle::default() {
+        return;
+    }
+    if let Some(last_span) = spans_vec.last_mut() {
+        if last_span.end_pos + 1 == pos && last_span.style == style {
+            last_span.end_pos = pos;
+            return;
+        }
+    }
+    spans_vec.push(StyleSpan {start_pos: pos, end_pos: pos, style});
+}
@@ -0,0 +1,65 @@
+// The color themes of the text, the search matches, the prompt and the reading guide.
+
+use crossterm::style::{Color, Color::Rgb, Color::*, Colors};
+
+const COLOR_REAL_BLACK: Color = Rgb {r: 0, g: 0, b: 0};
+const COLOR_AMBER: Color = Rgb {r: 255, g: 176, b: 0};
+const COLOR_PAPER: Color = Rgb {r: 250, g: 246, b: 236};
+
+pub struct Theme {
+    pub text: Colors,
+    pub search_match: Colors,
+    pub current_match: Colors,
+    pub prompt: Colors,
+    pub gutter: Colors,
+    pub guide: Colors,
+}
+
+pub const THEME_NAMES: [&str; 4] = ["green", "amber", "white", "paper"];
+
+impl Default for Theme {
+    /// The green of the old VT100 terminals.
+    fn default() -> Self {
+        Theme {
+            text: Colors::new(Green, COLOR_REAL_BLACK),
+            search_match: Colors::new(Blue, White),
+            current_match: Colors::new(DarkGrey, White),
+            prompt: Colors::new(White, DarkBlue),
+            gutter: Colors::new(DarkGrey, COLOR_REAL_BLACK),
+            guide: Colors::new(COLOR_REAL_BLACK, Green),
+        }
+    }
+}
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "green" => Some(Theme::default()),
+            "amber" => Some(Theme {
+                    text: Colors::new(COLOR_AMBER, COLOR_REAL_BLACK),
+                    search_match: Colors::new(COLOR_REAL_BLACK, COLOR_AMBER),
+                    current_match: Colors::new(COLOR_REAL_BLACK, White),
+                    prompt: Colors::new(COLOR_REAL_BLACK, COLOR_AMBER),
+                    gutter: Colors::new(DarkYellow, COLOR_REAL_BLACK),
+                    guide: Colors::new(COLOR_REAL_BLACK, DarkYellow),
+                }),
+            "white" => Some(Theme {
+                    text: Colors::new(Grey, COLOR_REAL_BLACK),
+                    search_match: Colors::new(COLOR_REAL_BLACK, Grey),
+                    current_match: Colors::new(COLOR_REAL_BLACK, Yellow),
+                    prompt: Colors::new(White, DarkBlue),
+                    gutter: Colors::new(DarkGrey, COLOR_REAL_BLACK),
+                    guide: Colors::new(White, DarkGrey),
+                }),
+            "paper" => Some(Theme {
+                    text: Colors::new(COLOR_REAL_BLACK, COLOR_PAPER),
+                    search_match: Colors::new(COLOR_REAL_BLACK, Rgb {r: 200, g: 220, b: 255}),
+                    current_match: Colors::new(COLOR_REAL_BLACK, Rgb {r: 255, g: 210, b: 80}),
+                    prompt: Colors::new(White, DarkBlue),
+                    gutter: Colors::new(Rgb {r: 150, g: 150, b: 150}, COLOR_PAPER),
+                    guide: Colors::new(COLOR_REAL_BLACK, Rgb {r: 200, g: 235, b: 200}),
+                }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// The table of contents, the chapters of a book and the screen where the
+// chapter to go to is chosen.
+
+use crossterm::{
+    event::{Event, KeyCode},
+    style::Colors,
+    Result,
+};
+
+use crate::keymap::{Action, KeyMap};
+use crate::backend::{CellStyle, Terminal};
+use crate::theme::Theme;
+
+pub struct Chapter {
+    pub title: String,
+    // The global position of the first char of the chapter in the text.
+    pub start_pos: usize,
+}
+
+/// The index of the chapter where the char is.
+pub fn chapter_at(chapters: &[Chapter], global_pos: usize) -> usize {
+    chapters.partition_point(|chapter| chapter.start_pos <= global_pos).saturating_sub(1)
+}
+
+/// Shows the table of contents and returns the index of the chosen chapter,
+/// or None when it is closed without choosing.
+pub fn choose_chapter(term: & mut dyn Terminal, chapters: &[Chapter], curr_index: usize, theme: &Theme,
+                      keymap: &KeyMap) -> Result<Option<usize>> {
+    let mut selected = curr_index.min(chapters.len().saturating_sub(1));
+    let mut first_shown = 0_usize;
+    loop {
+        // The title row, the chapters and the help row.
+        let (len_col, len_row) = term.size()?;
+        let num_rows = (len_row as usize).saturating_sub(2).max(1);
+        if selected < first_shown {
+            first_shown = selected;
+        } else if selected >= first_shown + num_rows {
+            first_shown = selected + 1 - num_rows;
+        }
+
+        term.clear(theme.text)?;
+        print_row(term, 0, "Table of contents", len_col, theme.prompt)?;
+        for (row, (index, chapter)) in chapters.iter().enumerate().skip(first_shown).take(num_rows).enumerate() {
+            let colors = if index == selected { theme.current_match } else { theme.text };
+            let entry = format!("{:>4}  {}", index + 1, chapter.title);
+            print_row(term, row as u16 + 1, &entry, len_col, colors)?;
+        }
+        print_row(term, len_row.saturating_sub(1), "Up, Down: choose the chapter   Enter: go to it   Esc: back",
+                  len_col, theme.prompt)?;
+
+        let event = term.read()?;
+        let action = keymap.action(&event);
+        if action == Some(Action::TableOfContents) || action == Some(Action::Quit) {
+            return Ok(None);
+        }
+        if let Event::Key(key_event) = event {
+            match key_event.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Enter => return Ok(Some(selected)),
+                KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(chapters.len() - 1),
+                KeyCode::PageUp => selected = selected.saturating_sub(num_rows),
+                KeyCode::PageDown => selected = (selected + num_rows).min(chapters.len() - 1),
+                KeyCode::Home => selected = 0,
+                KeyCode::End => selected = chapters.len() - 1,
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Prints the text on the row, truncated to the width of the terminal.
+pub fn print_row(term: & mut dyn Terminal, row: u16, text: &str, len_col: u16, colors: Colors) -> Result<()> {
+    let text: String = text.chars().take(len_col as usize).collect();
+    term.move_to(0, row)?;
+    term.print(&text, CellStyle::plain(colors))
+}
@@ -0,0 +1,79 @@
+// Restores the terminal when less_fp ends, also after a panic or a signal, so
+// that the shell isn't left in raw mode with the mouse capture on, or in the
+// alternate screen.
+
+use std::io::{self, stdout};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::process;
+#[cfg(unix)]
+use std::thread;
+
+use crossterm::{
+    cursor::Show,
+    event::DisableMouseCapture,
+    execute,
+    style::ResetColor,
+    terminal::{disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+#[cfg(unix)]
+use signal_hook::consts::signal::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+#[cfg(unix)]
+use signal_hook::iterator::Signals;
+
+static IN_ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+/// The pages are shown in the alternate screen, so that the contents and the
+/// scrollback of the main screen are kept and come back on exit.
+pub fn enter_alternate_screen() -> io::Result<()> {
+    execute!(stdout(), EnterAlternateScreen)?;
+    IN_ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// While it lives the terminal is in raw mode, when dropped the terminal is restored.
+pub struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Restores the terminal, the errors are ignored because it is the best effort on the way out.
+pub fn restore_terminal() {
+    let _ = execute!(stdout(), ResetColor, DisableMouseCapture, Show);
+    if IN_ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+    }
+    let _ = disable_raw_mode();
+}
+
+/// The panic message is printed after the terminal is restored, so that it is readable.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        restore_terminal();
+        default_hook(panic_info);
+    }));
+}
+
+/// The termination signals restore the terminal and exit with 128 + signal number,
+/// like the shell does.
+#[cfg(unix)]
+pub fn install_signal_handler() -> std::io::Result<()> {
+    let mut signals = Signals::new([SIGHUP, SIGINT, SIGQUIT, SIGTERM])?;
+    thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            restore_terminal();
+            process::exit(128 + signal);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn install_signal_handler() -> std::io::Result<()> {
+    Ok(())
+}
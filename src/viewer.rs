@@ -0,0 +1,214 @@
+// The state of the pages that are read, the text, its layout, the current
+// page, the search and the reading guide, and the commands that change it.
+// The keys are translated to commands, so the pages can also be driven by
+// the startup commands or by a script, and drawn again from the state.
+
+use less_fp::tokenize;
+
+use crate::guide::{guide_steps, Guide, GuideStep};
+use crate::layout::{page_rows, Layout, PageVec, TextArea};
+use crate::search::{Search, SearchMode, TextPos};
+use crate::settings::Settings;
+use crate::style::StyleSpan;
+
+/// A change of the state of the viewer.
+pub enum Command {
+    PrevPage,
+    NextPage,
+    FirstPage,
+    LastPage,
+    // The number of the page, starting at 1.
+    GoToPage(usize),
+    // The page with the char at the global position.
+    GoToPos(usize),
+    // The new size of the terminal.
+    Resize(u16, u16),
+    NarrowColumn,
+    WidenColumn,
+    ToggleCenter,
+    ToggleLineNumbers,
+    // The search string is entered char by char, and searched with the Enter key.
+    StartSearch,
+    SearchInput(char),
+    SearchBackspace,
+    SubmitSearch,
+    // Searches the pattern, ex: from the command line.
+    Search(String),
+    PrevMatch,
+    NextMatch,
+    ToggleGuide,
+    ToggleFixation,
+}
+
+pub struct Viewer<'a> {
+    pub text_vec: &'a [char],
+    pub styles: &'a [StyleSpan],
+    pub settings: Settings,
+    pub layout: Layout,
+    pub term_size: (u16, u16),
+    pub text_area: TextArea,
+    pub pages_vec: PageVec,
+    pub search_mode: SearchMode,
+    pub search_string: String,
+    pub search_opt: Option<Search>,
+    pub guide: Option<Guide>,
+}
+
+impl<'a> Viewer<'a> {
+    /// The text paginated for the size of the terminal, at the first page.
+    pub fn new(text_vec: &'a [char], styles: &'a [StyleSpan], settings: Settings, layout: Layout,
+               term_size: (u16, u16)) -> Self {
+        let text_area = layout.text_area(term_size, text_vec);
+        let pages_vec = PageVec::paginate(text_vec, &text_area);
+        Viewer {
+            text_vec,
+            styles,
+            settings,
+            layout,
+            term_size,
+            text_area,
+            pages_vec,
+            search_mode: SearchMode::NotInMode,
+            search_string: String::new(),
+            search_opt: None,
+            guide: None,
+        }
+    }
+
+    /// The chars of the current page.
+    pub fn page_text(&self) -> &'a [char] {
+        let (_page_num, page) = self.pages_vec.get_curr_page();
+        // The page of an empty text has no chars.
+        self.text_vec.get(page.global_start_char_pos..=page.global_stop_char_pos).unwrap_or_default()
+    }
+
+    /// The steps of the reading guide on the current page, none when the guide is hidden.
+    pub fn guide_steps(&self) -> Vec<GuideStep> {
+        let Some(ref guide) = self.guide else {
+            return Vec::new();
+        };
+        let p_buf = self.page_text();
+        let words: Vec<(usize, usize)> = tokenize(p_buf, self.settings.fixation).iter()
+                                                                                  .map(|word| (word.start, word.end))
+                                                                                  .collect();
+        guide_steps(p_buf, &words, &page_rows(p_buf, &self.text_area), guide.mode)
+    }
+
+    /// Changes the state, returns true when the page has to be drawn again.
+    pub fn apply(& mut self, command: Command) -> bool {
+        match command {
+            Command::PrevPage => self.pages_vec.prev_page(),
+            Command::NextPage => self.pages_vec.next_page(),
+            Command::FirstPage => self.pages_vec.set_curr_page_num(0),
+            Command::LastPage => self.pages_vec.set_curr_page_num(self.pages_vec.num_pages() - 1),
+            Command::GoToPage(page_num) => {
+                    let page_num = page_num.clamp(1, self.pages_vec.num_pages());
+                    self.pages_vec.set_curr_page_num(page_num - 1)
+                },
+            Command::GoToPos(global_pos) => {
+                    let page_num = self.pages_vec.find_char_pos_in_pages(global_pos.min(self.text_vec.len().saturating_sub(1)));
+                    self.pages_vec.set_curr_page_num(page_num)
+                },
+            Command::Resize(len_col, len_row) => {
+                    self.term_size = (len_col, len_row);
+                    self.relayout();
+                    true
+                },
+            // Narrow, widen or center the reading column, or show the line numbers.
+            Command::NarrowColumn => {
+                    self.layout.narrow(&self.text_area);
+                    self.relayout();
+                    true
+                },
+            Command::WidenColumn => {
+                    self.layout.widen(&self.text_area, self.term_size);
+                    self.relayout();
+                    true
+                },
+            Command::ToggleCenter => {
+                    self.layout.center = !self.layout.center;
+                    self.relayout();
+                    true
+                },
+            Command::ToggleLineNumbers => {
+                    self.layout.line_numbers = !self.layout.line_numbers;
+                    self.relayout();
+                    true
+                },
+            Command::StartSearch => {
+                    self.search_string.clear();
+                    self.search_mode = SearchMode::EnteringSearchString;
+                    true
+                },
+            Command::SearchInput(c) => {
+                    self.search_string.push(c);
+                    true
+                },
+            Command::SearchBackspace => {
+                    self.search_string.pop();
+                    true
+                },
+            Command::SubmitSearch => {
+                    self.search();
+                    true
+                },
+            Command::Search(pattern) => {
+                    self.search_string = pattern;
+                    self.search();
+                    true
+                },
+            Command::PrevMatch | Command::NextMatch => {
+                    let (SearchMode::BrowsingInSearch, Some(ref mut search_tmp)) = (&self.search_mode, & mut self.search_opt) else {
+                        return false;
+                    };
+                    // We jump to the prev or next word find occurrence.
+                    let moved = match command {
+                        Command::PrevMatch => search_tmp.prev_pos(),
+                        _ => search_tmp.next_pos(),
+                    };
+                    if moved {
+                        let (_ocurr_index, TextPos { start_pos, end_pos: _ }) = search_tmp.get_curr_pos();
+                        let page_num = self.pages_vec.find_char_pos_in_pages(*start_pos);
+                        self.pages_vec.set_curr_page_num(page_num);
+                    }
+                    moved
+                },
+            // Shows the reading guide by words, then by rows, then hides it.
+            Command::ToggleGuide => {
+                    self.guide = Guide::next_mode(self.guide.take());
+                    true
+                },
+            Command::ToggleFixation => {
+                    self.settings.no_fixation = !self.settings.no_fixation;
+                    true
+                },
+        }
+    }
+
+    /// Paginates again for the layout and the size of the terminal, at the page of the same text.
+    fn relayout(& mut self) {
+        self.text_area = self.layout.text_area(self.term_size, self.text_vec);
+        self.pages_vec.repaginate(self.text_vec, &self.text_area);
+    }
+
+    /// Searches the search string and goes to the page of the nearest occurrence,
+    /// or leaves the search mode when it isn't found.
+    fn search(& mut self) {
+        let found = if self.search_string.is_empty() {
+            None
+        } else {
+            Search::find(self.text_vec, &self.search_string, &self.settings.search_options)
+        };
+        let Some(mut search_tmp) = found else {
+            self.search_opt = None;
+            self.search_mode = SearchMode::NotInMode;
+            return;
+        };
+        self.search_mode = SearchMode::BrowsingInSearch;
+        let (_page_num, page) = self.pages_vec.get_curr_page();
+        let search_next_pos = search_tmp.find_next_nearest_pos(self.text_vec, page.global_start_char_pos);
+        let page_num = self.pages_vec.find_char_pos_in_pages(search_next_pos);
+        self.search_opt = Some(search_tmp);
+        self.pages_vec.set_curr_page_num(page_num);
+    }
+}